@@ -0,0 +1,619 @@
+//! Generates serde types from saved IEX response JSON.
+//!
+//! Usage:
+//!   $ curl https://api.iextrading.com/1.0/stock/aapl/quote > aapl.json
+//!   $ curl https://api.iextrading.com/1.0/stock/spy/quote > spy.json
+//!   $ cargo run --bin gen_serde_types -- --name Quote aapl.json spy.json
+//!
+//! Every sample is merged before types are inferred: fields that are null or
+//! absent in any sample become `Option`, numbers are `u64`/`i64` unless a
+//! sample had a fraction, and strings with only a few distinct values across
+//! enough samples become `string_enum!`s. Top-level arrays count each element
+//! as a sample.
+//!
+//! With `--diff`, the inferred struct is compared against the struct of the
+//! same name in `src/types.rs` (or `--types <path>`) instead of printed, and
+//! the process exits with status 1 if they differ.
+extern crate serde_json;
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "usage: gen_serde_types [--name <Type>] [--enum-max <n>] \
+                     [--diff [--types <path>]] <sample.json>...";
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while",
+];
+
+/// Everything seen for one JSON key across all samples.
+#[derive(Default)]
+struct Field {
+    /// Number of samples with a non-null value.
+    present: usize,
+    nulls: usize,
+    bools: usize,
+    unsigned: usize,
+    signed: usize,
+    floats: usize,
+    strings: BTreeMap<String, usize>,
+    /// Strings that parse as numbers.
+    numeric_strings: usize,
+    object: Option<Box<Shape>>,
+    elements: Option<Box<Field>>,
+    arrays: usize,
+}
+
+/// The merged shape of a JSON object.
+#[derive(Default)]
+struct Shape {
+    samples: usize,
+    /// Keys in the order they were first seen. serde_json sorts the keys of
+    /// each object, so this is alphabetical within a sample.
+    fields: Vec<(String, Field)>,
+}
+
+impl Shape {
+    fn add(&mut self, object: &serde_json::Map<String, Value>) {
+        self.samples += 1;
+        for (key, value) in object {
+            let index = match self.fields.iter().position(|f| f.0 == *key) {
+                Some(index) => index,
+                None => {
+                    self.fields.push((key.clone(), Field::default()));
+                    self.fields.len() - 1
+                }
+            };
+            self.fields[index].1.add(value);
+        }
+    }
+}
+
+impl Field {
+    fn add(&mut self, value: &Value) {
+        if !value.is_null() {
+            self.present += 1;
+        }
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.bools += 1,
+            Value::Number(n) => {
+                if n.is_u64() {
+                    self.unsigned += 1;
+                } else if n.is_i64() {
+                    self.signed += 1;
+                } else {
+                    self.floats += 1;
+                }
+            }
+            Value::String(s) => {
+                if s.parse::<f64>().is_ok() {
+                    self.numeric_strings += 1;
+                }
+                *self.strings.entry(s.clone()).or_insert(0) += 1;
+            }
+            Value::Object(o) => self
+                .object
+                .get_or_insert_with(|| Box::new(Shape::default()))
+                .add(o),
+            Value::Array(a) => {
+                self.arrays += 1;
+                let elements = self.elements.get_or_insert_with(|| Box::new(Field::default()));
+                for element in a {
+                    elements.add(element);
+                }
+            }
+        }
+    }
+
+    fn numbers(&self) -> usize {
+        self.unsigned + self.signed + self.floats
+    }
+
+    fn string_count(&self) -> usize {
+        self.strings.values().sum()
+    }
+}
+
+/// A generated type.
+enum Item {
+    Struct {
+        name: String,
+        fields: Vec<GenField>,
+    },
+    Enum {
+        name: String,
+        values: Vec<String>,
+    },
+}
+
+/// How a generated field's type should be deserialized.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    /// Numbers that are sometimes sent as strings.
+    Mixed,
+    Other,
+}
+
+struct GenField {
+    json_name: String,
+    name: String,
+    ty: String,
+    attrs: Vec<String>,
+}
+
+struct Generator {
+    enum_max: usize,
+    items: Vec<Item>,
+}
+
+impl Generator {
+    fn gen_struct(&mut self, name: &str, shape: &Shape) {
+        let mut fields = Vec::new();
+        for (key, field) in &shape.fields {
+            let optional = field.nulls > 0 || field.present < shape.samples;
+            fields.push(self.gen_field(name, key, field, optional));
+        }
+        self.items.push(Item::Struct {
+            name: name.to_string(),
+            fields,
+        });
+    }
+
+    fn gen_field(&mut self, parent: &str, key: &str, field: &Field, optional: bool) -> GenField {
+        let mut name = snake_case(key);
+        if KEYWORDS.contains(&name.as_str()) {
+            name = format!("{}_{}", snake_case(parent), name);
+        }
+
+        let mut attrs = Vec::new();
+        if camel_case(&name) != key {
+            attrs.push(format!("rename = \"{}\"", key));
+        }
+
+        let (ty, kind) = self.gen_type(parent, key, field);
+        let ty = match kind {
+            Kind::Mixed | Kind::Number if optional || kind == Kind::Mixed => {
+                attrs.push("default, deserialize_with = \"de::option_number\"".into());
+                format!("Option<{}>", ty)
+            }
+            _ if optional => {
+                attrs.push("default".into());
+                format!("Option<{}>", ty)
+            }
+            Kind::Number if ty == "u64" => {
+                attrs.push("deserialize_with = \"de::lenient_u64\"".into());
+                ty
+            }
+            _ => ty,
+        };
+
+        GenField {
+            json_name: key.to_string(),
+            name,
+            ty,
+            attrs,
+        }
+    }
+
+    fn gen_type(&mut self, parent: &str, key: &str, field: &Field) -> (String, Kind) {
+        let kinds = [
+            field.bools,
+            field.numbers(),
+            field.string_count(),
+            field.object.is_some() as usize,
+            field.arrays,
+        ];
+        let kind_count = kinds.iter().filter(|&&n| n > 0).count();
+        let strings = field.string_count();
+
+        if kind_count == 0 {
+            return ("Value".into(), Kind::Other);
+        }
+        if field.numbers() > 0 && strings > 0 && kind_count == 2 && field.numeric_strings == strings {
+            return ("f64".into(), Kind::Mixed);
+        }
+        if kind_count > 1 {
+            return ("Value".into(), Kind::Other);
+        }
+
+        if field.bools > 0 {
+            ("bool".into(), Kind::Other)
+        } else if field.floats > 0 {
+            ("f64".into(), Kind::Number)
+        } else if field.signed > 0 {
+            ("i64".into(), Kind::Number)
+        } else if field.unsigned > 0 {
+            ("u64".into(), Kind::Number)
+        } else if strings > 0 {
+            let distinct = field.strings.len();
+            if strings >= 3 && distinct * 2 <= strings && distinct <= self.enum_max {
+                let name = format!("{}{}", parent, pascal_case(key));
+                self.items.push(Item::Enum {
+                    name: name.clone(),
+                    values: field.strings.keys().cloned().collect(),
+                });
+                (name, Kind::Other)
+            } else {
+                ("String".into(), Kind::Other)
+            }
+        } else if let Some(ref shape) = field.object {
+            let name = pascal_case(&singular(key));
+            self.gen_struct(&name, shape);
+            (name, Kind::Other)
+        } else {
+            let elements = field.elements.as_ref().unwrap();
+            let ty = match elements.object {
+                Some(ref shape) if elements.numbers() + elements.string_count() == 0 => {
+                    let name = pascal_case(&singular(key));
+                    self.gen_struct(&name, shape);
+                    name
+                }
+                _ => self.gen_type(parent, &singular(key), elements).0,
+            };
+            (format!("Vec<{}>", ty), Kind::Other)
+        }
+    }
+}
+
+fn render(items: &[Item]) -> String {
+    let mut out = String::new();
+    for item in items {
+        match item {
+            Item::Enum { name, values } => {
+                out.push_str("string_enum! {\n");
+                out.push_str(&format!("    pub enum {} {{\n", name));
+                for value in values.iter().filter(|v| !v.is_empty()) {
+                    out.push_str(&format!("        {} => {:?},\n", variant_name(value), value));
+                }
+                out.push_str("    }\n}\n\n");
+            }
+            Item::Struct { name, fields } => {
+                out.push_str("#[serde(rename_all = \"camelCase\")]\n");
+                out.push_str("#[derive(Serialize, Deserialize, Debug)]\n");
+                out.push_str(&format!("pub struct {} {{\n", name));
+                for field in fields {
+                    if !field.attrs.is_empty() {
+                        out.push_str(&format!("    #[serde({})]\n", field.attrs.join(", ")));
+                    }
+                    out.push_str(&format!("    pub {}: {},\n", field.name, field.ty));
+                }
+                out.push_str("    #[serde(flatten, skip_serializing)]\n");
+                out.push_str("    pub extra: Map<String, Value>,\n");
+                out.push_str("}\n\n");
+            }
+        }
+    }
+    out
+}
+
+/// Converts a JSON key to a snake_case field name, e.g. `EPSReportDate` to
+/// `eps_report_date` and `week52High` to `week52_high`.
+fn snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(p) if p.is_lowercase() || p.is_numeric() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+            if boundary && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out
+}
+
+/// serde's `rename_all = "camelCase"` conversion of a snake_case field name.
+fn camel_case(field: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn pascal_case(key: &str) -> String {
+    snake_case(key)
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap();
+            first.to_uppercase().chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn singular(key: &str) -> String {
+    if let Some(stem) = key.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if key.ends_with("ss") {
+        key.to_string()
+    } else {
+        key.strip_suffix('s').unwrap_or(key).to_string()
+    }
+}
+
+fn variant_name(value: &str) -> String {
+    let name = if value.chars().all(|c| !c.is_lowercase()) {
+        pascal_case(&value.to_lowercase())
+    } else {
+        pascal_case(value)
+    };
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => name,
+        _ => format!("V{}", name),
+    }
+}
+
+/// Reads `pub struct <name>` from Rust source as `(field, type)` pairs.
+fn parse_struct(source: &str, name: &str) -> Option<Vec<(String, String)>> {
+    let header = format!("pub struct {} {{", name);
+    let start = source.find(&header)? + header.len();
+    let body = &source[start..start + source[start..].find("\n}")?];
+    Some(
+        body.lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("pub "))
+            .filter_map(|line| {
+                let line = line.trim_start_matches("pub ").trim_end_matches(',');
+                let colon = line.find(':')?;
+                Some((line[..colon].trim().to_string(), line[colon + 1..].trim().to_string()))
+            })
+            .filter(|field| field.0 != "extra")
+            .collect(),
+    )
+}
+
+/// Reduces a type to what `--diff` compares: `Money` counts as `f64`, and
+/// enums and structs count as `String`-like named types.
+fn comparable(ty: &str) -> String {
+    for wrapper in &["Option<", "Vec<"] {
+        if ty.starts_with(wrapper) && ty.ends_with('>') {
+            let inner = comparable(&ty[wrapper.len()..ty.len() - 1]);
+            return format!("{}{}>", wrapper, inner);
+        }
+    }
+    match ty {
+        "Money" => "f64".into(),
+        "u64" | "i64" | "f64" | "bool" | "Value" => ty.into(),
+        _ => "String".into(),
+    }
+}
+
+fn diff(existing: &[(String, String)], generated: &[GenField]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for field in generated {
+        match existing.iter().find(|e| e.0 == field.name) {
+            None => lines.push(format!(
+                "+ pub {}: {}, // \"{}\"",
+                field.name, field.ty, field.json_name
+            )),
+            Some((_, ty)) if comparable(ty) != comparable(&field.ty) => {
+                lines.push(format!("~ pub {}: {} -> {},", field.name, ty, field.ty))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, ty) in existing {
+        if !generated.iter().any(|field| field.name == *name) {
+            lines.push(format!("- pub {}: {},", name, ty));
+        }
+    }
+    lines
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
+
+fn main() {
+    let mut name = "NewTypeRenameMe".to_string();
+    let mut types_path = "src/types.rs".to_string();
+    let mut enum_max = 6;
+    let mut diff_mode = false;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => name = args.next().unwrap_or_else(|| fail(USAGE)),
+            "--types" => types_path = args.next().unwrap_or_else(|| fail(USAGE)),
+            "--enum-max" => {
+                enum_max = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| fail(USAGE))
+            }
+            "--diff" => diff_mode = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        fail(USAGE);
+    }
+
+    let mut shape = Shape::default();
+    for file in &files {
+        let json = fs::read_to_string(file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+        let value: Value =
+            serde_json::from_str(&json).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+        match value {
+            Value::Object(ref object) => shape.add(object),
+            Value::Array(ref samples) => {
+                for sample in samples {
+                    match sample.as_object() {
+                        Some(object) => shape.add(object),
+                        None => fail(&format!("{}: array elements must be objects", file)),
+                    }
+                }
+            }
+            _ => fail(&format!("{}: expected an object or an array", file)),
+        }
+    }
+
+    let mut generator = Generator {
+        enum_max,
+        items: Vec::new(),
+    };
+    generator.gen_struct(&name, &shape);
+
+    if !diff_mode {
+        print!("{}", render(&generator.items));
+        return;
+    }
+
+    let source =
+        fs::read_to_string(&types_path).unwrap_or_else(|e| fail(&format!("{}: {}", types_path, e)));
+    let existing = parse_struct(&source, &name)
+        .unwrap_or_else(|| fail(&format!("{}: no struct named {}", types_path, name)));
+    let generated = match generator.items.last() {
+        Some(Item::Struct { fields, .. }) => fields,
+        _ => unreachable!(),
+    };
+
+    let lines = diff(&existing, generated);
+    for line in &lines {
+        println!("{}", line);
+    }
+    if !lines.is_empty() {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(name: &str, samples: &[&str]) -> Vec<Item> {
+        let mut shape = Shape::default();
+        for sample in samples {
+            let value: Value = serde_json::from_str(sample).unwrap();
+            shape.add(value.as_object().unwrap());
+        }
+        let mut generator = Generator {
+            enum_max: 6,
+            items: Vec::new(),
+        };
+        generator.gen_struct(name, &shape);
+        generator.items
+    }
+
+    #[test]
+    fn converts_iex_keys_to_field_names() {
+        assert_eq!(snake_case("EPSReportDate"), "eps_report_date");
+        assert_eq!(snake_case("isISO"), "is_iso");
+        assert_eq!(snake_case("week52High"), "week52_high");
+        assert_eq!(snake_case("CEO"), "ceo");
+        assert_eq!(camel_case("week52_high"), "week52High");
+        assert_eq!(pascal_case("systemEvent"), "SystemEvent");
+    }
+
+    #[test]
+    fn merges_samples_to_infer_options_and_numbers() {
+        let items = generate(
+            "Quote",
+            &[
+                r#"{"symbol": "AAPL", "volume": 100, "peRatio": 17.5, "open": 171.0}"#,
+                r#"{"symbol": "SPY", "volume": 200, "peRatio": null, "open": 264}"#,
+                r#"{"symbol": "DBX", "volume": 300}"#,
+            ],
+        );
+        let out = render(&items);
+        assert!(out.contains("    pub symbol: String,\n"));
+        assert!(out.contains(
+            "    #[serde(deserialize_with = \"de::lenient_u64\")]\n    pub volume: u64,\n"
+        ));
+        assert!(out.contains(
+            "    #[serde(default, deserialize_with = \"de::option_number\")]\n    pub pe_ratio: Option<f64>,\n"
+        ));
+        assert!(out.contains("    pub open: Option<f64>,\n"));
+    }
+
+    #[test]
+    fn low_cardinality_strings_become_enums() {
+        let items = generate(
+            "SystemEvent",
+            &[
+                r#"{"systemEvent": "O"}"#,
+                r#"{"systemEvent": "R"}"#,
+                r#"{"systemEvent": "R"}"#,
+                r#"{"systemEvent": "C"}"#,
+                r#"{"systemEvent": "O"}"#,
+                r#"{"systemEvent": "R"}"#,
+            ],
+        );
+        let out = render(&items);
+        assert!(out.contains("pub enum SystemEventSystemEvent {\n        C => \"C\",\n        O => \"O\",\n        R => \"R\",\n"));
+        assert!(out.contains("    pub system_event: SystemEventSystemEvent,\n"));
+    }
+
+    #[test]
+    fn nested_objects_and_arrays_become_structs() {
+        let items = generate(
+            "Book",
+            &[r#"{"quote": {"symbol": "AAPL"}, "bids": [{"price": 1.5}], "isISO": true, "type": "cs"}"#],
+        );
+        let out = render(&items);
+        assert!(out.contains("pub struct Quote {"));
+        assert!(out.contains("pub struct Bid {"));
+        assert!(out.contains("    pub bids: Vec<Bid>,\n"));
+        assert!(out.contains("    #[serde(rename = \"isISO\")]\n    pub is_iso: bool,\n"));
+        assert!(out.contains("    #[serde(rename = \"type\")]\n    pub book_type: String,\n"));
+    }
+
+    #[test]
+    fn diffs_against_existing_structs() {
+        let source = "pub struct Quote {\n    pub symbol: String,\n    #[serde(default)]\n    pub open: Money,\n    pub gone: bool,\n    #[serde(flatten, skip_serializing)]\n    pub extra: Map<String, Value>,\n}\n";
+        let existing = parse_struct(source, "Quote").unwrap();
+        assert_eq!(existing.len(), 3);
+
+        let items = generate(
+            "Quote",
+            &[r#"{"symbol": "AAPL", "open": 1.5, "iexOpen": 1.5}"#, r#"{"symbol": "SPY", "open": null}"#],
+        );
+        let generated = match items.last() {
+            Some(Item::Struct { fields, .. }) => fields,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            diff(&existing, generated),
+            vec![
+                "+ pub iex_open: Option<f64>, // \"iexOpen\"",
+                "~ pub open: Money -> Option<f64>,",
+                "- pub gone: bool,",
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Response types for endpoints only available on IEX Cloud.
+use de;
+use serde_json::{Map, Value};
+use types::Money;
+
+/// Response of the `advanced-stats` endpoint: the key stats plus valuation
+/// and balance sheet ratios.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AdvancedStats {
+    pub company_name: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub marketcap: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52low: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52change: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shares_outstanding: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub float: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub avg10_volume: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub avg30_volume: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day200_moving_avg: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day50_moving_avg: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub employees: Option<u64>,
+    #[serde(rename = "ttmEPS")]
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ttm_eps: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ttm_dividend_rate: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub dividend_yield: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub next_dividend_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub ex_dividend_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub next_earnings_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_ratio: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub beta: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub max_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year5_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year2_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year1_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ytd_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month6_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month3_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month1_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day30_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day5_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_cash: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_debt: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub gross_profit: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_revenue: Option<Money>,
+    #[serde(rename = "EBITDA")]
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ebitda: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue_per_share: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue_per_employee: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub debt_to_equity: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub profit_margin: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub enterprise_value: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub enterprise_value_to_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_to_sales: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_to_book: Option<f64>,
+    #[serde(rename = "forwardPERatio")]
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub forward_pe_ratio: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub peg_ratio: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_high: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_low: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub week52high_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub week52low_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub put_call_ratio: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `estimates` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Estimates {
+    pub symbol: String,
+    pub estimates: Vec<Estimate>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Estimate {
+    #[serde(rename = "consensusEPS")]
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub consensus_eps: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub number_of_estimates: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub fiscal_period: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub fiscal_end_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub report_date: Option<String>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `price-target` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PriceTarget {
+    pub symbol: String,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub updated_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_target_average: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_target_high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_target_low: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub number_of_analysts: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// One period of the `recommendation-trends` endpoint. Ratings count the
+/// analysts at each level; `rating_scale_mark` runs from 1 (buy) to 3 (sell).
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecommendationTrend {
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub consensus_end_date: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub consensus_start_date: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub corporate_actions_applied_date: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_buy: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_overweight: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_hold: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_underweight: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_sell: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_none: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub rating_scale_mark: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// A holder listed by the `fund-ownership` or `institutional-ownership`
+/// endpoints. Adjusted figures account for splits since the report date.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Owner {
+    pub entity_proper_name: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub report_date: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub adj_holding: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub adj_mv: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub reported_holding: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub reported_mv: Option<Money>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// One entry of the `insider-transactions` endpoint. `tran_shares` is
+/// negative for sales.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InsiderTransaction {
+    pub full_name: String,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub reported_title: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub effective_date: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub filing_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub tran_code: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub tran_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub tran_shares: Option<i64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub tran_value: Option<Money>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// One entry of the `insider-roster` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Insider {
+    pub entity_name: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub position: Option<i64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub report_date: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
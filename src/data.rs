@@ -0,0 +1,214 @@
+//! Responses of `DataEndpoint` and currency conversion with FX rates.
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+use de;
+use endpoints::DataEndpoint;
+use serde_json::{Map, Value};
+use types::{Money, Quote};
+use {Client, Endpoint, Response, Result};
+
+/// An exchange rate from `fx/latest`, `fx/convert` or `fx/historical`.
+/// `symbol` is the currency pair, e.g. `USDCAD` is the number of Canadian
+/// dollars per US dollar.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FxRate {
+    pub symbol: String,
+    pub rate: Money,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub timestamp: Option<u64>,
+    /// Set by `fx/historical`.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Set by `fx/convert`: the converted amount.
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub amount: Option<Money>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// One observation of an economic time series, such as a treasury rate.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Observation {
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub subkey: Option<String>,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub date: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub updated: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// `MissingRate` is returned when converting between currencies that no
+/// known rate connects.
+#[derive(Debug)]
+pub struct MissingRate {
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for MissingRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no FX rate from {} to {}", self.from, self.to)
+    }
+}
+
+impl error::Error for MissingRate {}
+
+/// `FxRates` looks up exchange rates by currency pair, using the inverse
+/// pair when only that is known.
+#[derive(Debug, Default)]
+pub struct FxRates {
+    rates: HashMap<String, Money>,
+}
+
+impl FxRates {
+    pub fn new<I>(rates: I) -> FxRates
+    where
+        I: IntoIterator<Item = FxRate>,
+    {
+        FxRates {
+            rates: rates
+                .into_iter()
+                .map(|rate| (rate.symbol.to_uppercase(), rate.rate))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of `to` per `from`, e.g. `rate("USD", "CAD")`.
+    pub fn rate(&self, from: &str, to: &str) -> Option<Money> {
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        if from == to {
+            return Some(Money::from(1u8));
+        }
+        if let Some(&rate) = self.rates.get(&format!("{}{}", from, to)) {
+            return Some(rate);
+        }
+        match self.rates.get(&format!("{}{}", to, from)) {
+            Some(&rate) if rate != Money::from(0u8) => Some(Money::from(1u8) / rate),
+            _ => None,
+        }
+    }
+
+    /// Converts `amount` from one currency into another.
+    pub fn convert(&self, amount: Money, from: &str, to: &str) -> Result<Money> {
+        Ok(amount * self.rate_or_err(from, to)?)
+    }
+
+    /// Converts every price in `quote` from one currency into another.
+    /// Percentages and ratios are left as they are.
+    pub fn convert_quote(&self, quote: &mut Quote, from: &str, to: &str) -> Result<()> {
+        let rate = self.rate_or_err(from, to)?;
+        quote.latest_price *= rate;
+        for price in vec![
+            &mut quote.open,
+            &mut quote.close,
+            &mut quote.high,
+            &mut quote.low,
+            &mut quote.iex_realtime_price,
+            &mut quote.delayed_price,
+            &mut quote.previous_close,
+            &mut quote.change,
+            &mut quote.iex_bid_price,
+            &mut quote.iex_ask_price,
+            &mut quote.market_cap,
+            &mut quote.week52_high,
+            &mut quote.week52_low,
+        ] {
+            if let Some(price) = price.as_mut() {
+                *price *= rate;
+            }
+        }
+        Ok(())
+    }
+
+    fn rate_or_err(&self, from: &str, to: &str) -> Result<Money> {
+        self.rate(from, to).ok_or_else(|| {
+            MissingRate {
+                from: from.to_string(),
+                to: to.to_string(),
+            }.into()
+        })
+    }
+}
+
+impl Client {
+    /// Requests one of IEX Cloud's non-stock data endpoints.
+    pub fn data_request(&self, req: DataEndpoint) -> Result<Response> {
+        Ok(::reqwest::get(&self.url(&req.to_endpoint()))?.json()?)
+    }
+
+    /// Fetches the latest rates for `symbols`, ready for conversions.
+    pub fn fx_rates<S>(&self, symbols: &[S]) -> Result<FxRates>
+    where
+        S: AsRef<str>,
+    {
+        let rates: Vec<FxRate> = self
+            .data_request(DataEndpoint::FxLatest {
+                symbols: symbols.iter().map(|s| s.as_ref().into()).collect(),
+            })?
+            .try_into()?;
+        Ok(FxRates::new(rates))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn rates() -> FxRates {
+        let rates: Vec<FxRate> = serde_json::from_value(json!([
+            {"symbol": "USDCAD", "rate": 1.25, "timestamp": 1583971200000u64},
+            {"symbol": "EURUSD", "rate": 1.25, "timestamp": 1583971200000u64},
+        ])).unwrap();
+        FxRates::new(rates)
+    }
+
+    fn money(s: &str) -> Money {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rates_use_direct_inverse_and_identity_pairs() {
+        let rates = rates();
+        assert_eq!(rates.rate("USD", "CAD"), Some(money("1.25")));
+        assert_eq!(rates.rate("usd", "eur"), Some(money("0.8")));
+        assert_eq!(rates.rate("GBP", "GBP"), Some(money("1")));
+        assert_eq!(rates.rate("USD", "GBP"), None);
+    }
+
+    #[test]
+    fn convert_fails_without_a_rate() {
+        let rates = rates();
+        assert_eq!(rates.convert(money("10"), "USD", "CAD").unwrap(), money("12.5"));
+        let err = rates.convert(money("10"), "USD", "JPY").unwrap_err();
+        assert_eq!(err.to_string(), "no FX rate from USD to JPY");
+    }
+
+    #[test]
+    fn convert_quote_scales_prices_only() {
+        let mut quote: Quote =
+            serde_json::from_str(include_str!("../tests/fixtures/quote.json")).unwrap();
+        let change_percent = quote.change_percent;
+        let latest_price = quote.latest_price;
+        let open = quote.open.unwrap();
+
+        rates().convert_quote(&mut quote, "USD", "CAD").unwrap();
+        assert_eq!(quote.latest_price, latest_price * money("1.25"));
+        assert_eq!(quote.open, Some(open * money("1.25")));
+        assert_eq!(quote.change_percent, change_percent);
+    }
+}
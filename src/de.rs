@@ -0,0 +1,384 @@
+//! Lenient deserializers for fields where IEX is loose about the JSON type.
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Deserializes a non-negative integer, also accepting integral floats such
+/// as `1234.0`, which IEX returns for some volume and size fields.
+pub fn lenient_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(U64Visitor)
+}
+
+struct U64Visitor;
+
+impl<'de> Visitor<'de> for U64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        if v < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(v), &self));
+        }
+        Ok(v as u64)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<u64, E> {
+        if v.fract() != 0.0 || v < 0.0 || v >= u64::MAX as f64 {
+            return Err(E::invalid_value(de::Unexpected::Float(v), &self));
+        }
+        Ok(v as u64)
+    }
+}
+
+/// Deserializes an optional number that IEX may send as a JSON number, a
+/// numeric string, an empty string or `null`. Use together with
+/// `#[serde(default)]` so that a missing field is `None` too.
+///
+/// Values are parsed through `FromStr`, which keeps `Money` exact when the
+/// `decimal` feature is enabled and still accepts `1234.0` for integers.
+pub fn option_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    deserializer.deserialize_any(NumberVisitor(PhantomData))
+}
+
+struct NumberVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for NumberVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, a numeric string or null")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Option<T>, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Option<T>, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Option<T>, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Option<T>, E> {
+        let v = v.trim();
+        if v.is_empty() {
+            return Ok(None);
+        }
+        v.parse()
+            .map(Some)
+            .map_err(|e| E::custom(format_args!("invalid number {:?}: {}", v, e)))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Option<T>, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Option<T>, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Deserializes a number that IEX may send as a JSON number or a numeric
+/// string, such as HIST file sizes. Unlike `option_number`, it must be there.
+pub fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    option_number(deserializer)?.ok_or_else(|| de::Error::custom("expected a number"))
+}
+
+/// Deserializes an optional string that IEX may send as a number (usually a
+/// `0` placeholder for dates), an empty string or `null`. Numbers are kept in
+/// their textual form; empty strings become `None`.
+pub fn option_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(StringVisitor)
+}
+
+struct StringVisitor;
+
+impl<'de> Visitor<'de> for StringVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string, a number or null")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Option<String>, E> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Option<String>, E> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Option<String>, E> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Option<String>, E> {
+        if v.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(v.to_string()))
+        }
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Option<String>, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Option<String>, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Option<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Deserializes a timestamp sent either as an RFC 3339 string, such as
+/// `2017-06-29T13:14:22-04:00`, or as milliseconds since the epoch.
+pub fn datetime<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DateTimeVisitor)
+}
+
+struct DateTimeVisitor;
+
+impl<'de> Visitor<'de> for DateTimeVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an RFC 3339 timestamp or epoch milliseconds")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<DateTime<Utc>, E> {
+        if v > i64::MAX as u64 {
+            return Err(E::invalid_value(de::Unexpected::Unsigned(v), &self));
+        }
+        self.visit_i64(v as i64)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<DateTime<Utc>, E> {
+        Utc.timestamp_millis_opt(v)
+            .single()
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<DateTime<Utc>, E> {
+        DateTime::parse_from_rfc3339(v)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+/// Deserializes a comma-separated list, such as `"AAPL,MSFT"`, skipping
+/// empty entries. A JSON array of strings, as such lists serialize to, is
+/// accepted too.
+pub fn comma_separated<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(CommaSeparatedVisitor)
+}
+
+struct CommaSeparatedVisitor;
+
+impl<'de> Visitor<'de> for CommaSeparatedVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a comma-separated string or a list of strings")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Vec<String>, E> {
+        Ok(v
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<String>, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element::<String>()? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+/// Deserializes `null` as the type's default value, e.g. an empty list.
+pub fn default_on_null<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + ::serde::Deserialize<'de>,
+{
+    let value: Option<T> = ::serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[derive(Deserialize)]
+    struct Volume {
+        #[serde(deserialize_with = "lenient_u64")]
+        volume: u64,
+    }
+
+    fn volume(json: &str) -> Result<u64, serde_json::Error> {
+        serde_json::from_str::<Volume>(json).map(|v| v.volume)
+    }
+
+    #[test]
+    fn lenient_u64_accepts_integers_and_integral_floats() {
+        assert_eq!(volume(r#"{"volume": 1234}"#).unwrap(), 1234);
+        assert_eq!(volume(r#"{"volume": 1234.0}"#).unwrap(), 1234);
+        assert_eq!(
+            volume(r#"{"volume": 18446744073709551615}"#).unwrap(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn lenient_u64_rejects_fractions_and_negatives() {
+        assert!(volume(r#"{"volume": 1234.5}"#).is_err());
+        assert!(volume(r#"{"volume": -1}"#).is_err());
+        assert!(volume(r#"{"volume": "1234"}"#).is_err());
+    }
+
+    #[derive(Deserialize)]
+    struct Lenient {
+        #[serde(default, deserialize_with = "option_number")]
+        price: Option<f64>,
+        #[serde(default, deserialize_with = "option_number")]
+        size: Option<u64>,
+    }
+
+    fn lenient(json: &str) -> Result<(Option<f64>, Option<u64>), serde_json::Error> {
+        serde_json::from_str::<Lenient>(json).map(|l| (l.price, l.size))
+    }
+
+    #[test]
+    fn option_number_accepts_numbers_strings_and_nulls() {
+        assert_eq!(
+            lenient(r#"{"price": 172.68, "size": 100}"#).unwrap(),
+            (Some(172.68), Some(100))
+        );
+        assert_eq!(
+            lenient(r#"{"price": "172.68", "size": 100.0}"#).unwrap(),
+            (Some(172.68), Some(100))
+        );
+        assert_eq!(
+            lenient(r#"{"price": null, "size": ""}"#).unwrap(),
+            (None, None)
+        );
+        assert_eq!(lenient("{}").unwrap(), (None, None));
+    }
+
+    #[test]
+    fn option_number_rejects_garbage() {
+        assert!(lenient(r#"{"price": "n/a"}"#).is_err());
+        assert!(lenient(r#"{"size": 1.5}"#).is_err());
+        assert!(lenient(r#"{"size": true}"#).is_err());
+    }
+
+    #[derive(Deserialize)]
+    struct Size {
+        #[serde(deserialize_with = "number")]
+        size: u64,
+    }
+
+    #[test]
+    fn number_accepts_numeric_strings_but_not_empty_values() {
+        let size = |json| serde_json::from_str::<Size>(json).map(|s| s.size);
+        assert_eq!(size(r#"{"size": "5196935040"}"#).unwrap(), 5196935040);
+        assert_eq!(size(r#"{"size": 42}"#).unwrap(), 42);
+        assert!(size(r#"{"size": ""}"#).is_err());
+        assert!(size(r#"{"size": null}"#).is_err());
+    }
+
+    #[derive(Deserialize)]
+    struct Article {
+        #[serde(deserialize_with = "datetime")]
+        datetime: DateTime<Utc>,
+        #[serde(deserialize_with = "comma_separated")]
+        related: Vec<String>,
+    }
+
+    fn article(json: &str) -> Result<(i64, Vec<String>), serde_json::Error> {
+        serde_json::from_str::<Article>(json).map(|a| (a.datetime.timestamp(), a.related))
+    }
+
+    #[test]
+    fn datetime_accepts_rfc3339_and_epoch_millis() {
+        let expected = (1498756462, vec!["AAPL".to_string(), "MSFT".to_string()]);
+        assert_eq!(
+            article(r#"{"datetime": "2017-06-29T13:14:22-04:00", "related": "AAPL,MSFT"}"#)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            article(r#"{"datetime": 1498756462000, "related": "AAPL, MSFT,"}"#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            article(r#"{"datetime": 1498756462000, "related": ""}"#).unwrap().1,
+            Vec::<String>::new()
+        );
+        assert!(article(r#"{"datetime": "yesterday", "related": ""}"#).is_err());
+    }
+
+    #[test]
+    fn comma_separated_accepts_lists() {
+        assert_eq!(
+            article(r#"{"datetime": 1498756462000, "related": ["AAPL", "MSFT"]}"#).unwrap().1,
+            vec!["AAPL".to_string(), "MSFT".to_string()]
+        );
+        assert!(article(r#"{"datetime": 1498756462000, "related": [1]}"#).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! DEEP 1.0, IEX's depth-of-book binary feed, as carried by IEX-TP segments.
+//! Messages DEEP shares with TOPS decode to the same types.
+use serde_json::Map;
+
+use iextp::{DecodeError, Fields, Segment};
+use tops::{
+    millis, AuctionInformation, OfficialPrice, OperationalHalt, SecurityDirectory,
+    ShortSalePriceTest, SystemEventMessage, TradeReport, TradingStatusMessage,
+};
+use types::{Ask, Bid, Money};
+use Result;
+
+byte_enum! {
+    pub enum SecurityEventType {
+        /// The opening process is complete, including any opening auction.
+        OpeningProcessComplete => b'O',
+        /// The closing process is complete, including any closing auction.
+        ClosingProcessComplete => b'C',
+    }
+}
+
+/// The side of the book a `PriceLevelUpdate` changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// Marks the end of a security's opening or closing process.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityEvent<'a> {
+    pub event: SecurityEventType,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+}
+
+impl<'a> SecurityEvent<'a> {
+    fn decode(fields: &mut Fields<'a>) -> Result<SecurityEvent<'a>> {
+        Ok(SecurityEvent {
+            event: fields.u8()?.into(),
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+        })
+    }
+}
+
+/// The new aggregate size displayed at one price level of a security's book.
+/// A size of zero removes the level.
+///
+/// One order book event can update several levels. Every update but the last
+/// of an event leaves the book in transition, so only a book whose last
+/// update `is_event_complete` is consistent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceLevelUpdate<'a> {
+    pub side: Side,
+    pub flags: u8,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub size: u32,
+    pub price: Money,
+}
+
+impl<'a> PriceLevelUpdate<'a> {
+    fn decode(side: Side, fields: &mut Fields<'a>) -> Result<PriceLevelUpdate<'a>> {
+        Ok(PriceLevelUpdate {
+            side,
+            flags: fields.u8()?,
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            size: fields.u32()?,
+            price: fields.price()?,
+        })
+    }
+
+    /// Whether this is the last update of its order book event.
+    pub fn is_event_complete(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Returns the level as a `Bid`, if this is a buy update.
+    pub fn bid(&self) -> Option<Bid> {
+        if self.side != Side::Buy {
+            return None;
+        }
+        Some(Bid {
+            price: self.price,
+            size: u64::from(self.size),
+            timestamp: millis(self.timestamp),
+            extra: Map::new(),
+        })
+    }
+
+    /// Returns the level as an `Ask`, if this is a sell update.
+    pub fn ask(&self) -> Option<Ask> {
+        if self.side != Side::Sell {
+            return None;
+        }
+        Some(Ask {
+            price: self.price,
+            size: u64::from(self.size),
+            timestamp: millis(self.timestamp),
+            extra: Map::new(),
+        })
+    }
+}
+
+/// One message of the DEEP binary feed. Symbols and other text borrow from
+/// the segment. The streaming counterpart is `DeepMessage`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeepFeedMessage<'a> {
+    SystemEvent(SystemEventMessage),
+    SecurityDirectory(SecurityDirectory<'a>),
+    TradingStatus(TradingStatusMessage<'a>),
+    OperationalHalt(OperationalHalt<'a>),
+    ShortSalePriceTest(ShortSalePriceTest<'a>),
+    SecurityEvent(SecurityEvent<'a>),
+    PriceLevelUpdate(PriceLevelUpdate<'a>),
+    TradeReport(TradeReport<'a>),
+    OfficialPrice(OfficialPrice<'a>),
+    TradeBreak(TradeReport<'a>),
+    AuctionInformation(AuctionInformation<'a>),
+    /// A message type not (yet) known to this crate, undecoded.
+    Unknown { message_type: u8, data: &'a [u8] },
+}
+
+impl<'a> DeepFeedMessage<'a> {
+    /// Decodes one message of a DEEP segment.
+    pub fn parse(data: &'a [u8]) -> Result<DeepFeedMessage<'a>> {
+        let (&message_type, body) = data
+            .split_first()
+            .ok_or_else(|| DecodeError::new("empty DEEP message"))?;
+        let mut fields = Fields::new(body, "DEEP message");
+        let fields = &mut fields;
+        Ok(match message_type {
+            b'S' => DeepFeedMessage::SystemEvent(SystemEventMessage::decode(fields)?),
+            b'D' => DeepFeedMessage::SecurityDirectory(SecurityDirectory::decode(fields)?),
+            b'H' => DeepFeedMessage::TradingStatus(TradingStatusMessage::decode(fields)?),
+            b'O' => DeepFeedMessage::OperationalHalt(OperationalHalt::decode(fields)?),
+            b'P' => DeepFeedMessage::ShortSalePriceTest(ShortSalePriceTest::decode(fields)?),
+            b'E' => DeepFeedMessage::SecurityEvent(SecurityEvent::decode(fields)?),
+            b'8' => DeepFeedMessage::PriceLevelUpdate(PriceLevelUpdate::decode(Side::Buy, fields)?),
+            b'5' => {
+                DeepFeedMessage::PriceLevelUpdate(PriceLevelUpdate::decode(Side::Sell, fields)?)
+            }
+            b'T' => DeepFeedMessage::TradeReport(TradeReport::decode(fields)?),
+            b'X' => DeepFeedMessage::OfficialPrice(OfficialPrice::decode(fields)?),
+            b'B' => DeepFeedMessage::TradeBreak(TradeReport::decode(fields)?),
+            b'A' => DeepFeedMessage::AuctionInformation(AuctionInformation::decode(fields)?),
+            _ => DeepFeedMessage::Unknown {
+                message_type,
+                data: body,
+            },
+        })
+    }
+
+    /// Returns the symbol the message is about, if any.
+    pub fn symbol(&self) -> Option<&'a str> {
+        match *self {
+            DeepFeedMessage::SecurityDirectory(ref m) => Some(m.symbol),
+            DeepFeedMessage::TradingStatus(ref m) => Some(m.symbol),
+            DeepFeedMessage::OperationalHalt(ref m) => Some(m.symbol),
+            DeepFeedMessage::ShortSalePriceTest(ref m) => Some(m.symbol),
+            DeepFeedMessage::SecurityEvent(ref m) => Some(m.symbol),
+            DeepFeedMessage::PriceLevelUpdate(ref m) => Some(m.symbol),
+            DeepFeedMessage::TradeReport(ref m) | DeepFeedMessage::TradeBreak(ref m) => {
+                Some(m.symbol)
+            }
+            DeepFeedMessage::OfficialPrice(ref m) => Some(m.symbol),
+            DeepFeedMessage::AuctionInformation(ref m) => Some(m.symbol),
+            DeepFeedMessage::SystemEvent(_) | DeepFeedMessage::Unknown { .. } => None,
+        }
+    }
+
+    /// Returns when the message was sent, in nanoseconds since the epoch.
+    pub fn timestamp(&self) -> Option<u64> {
+        match *self {
+            DeepFeedMessage::SystemEvent(ref m) => Some(m.timestamp),
+            DeepFeedMessage::SecurityDirectory(ref m) => Some(m.timestamp),
+            DeepFeedMessage::TradingStatus(ref m) => Some(m.timestamp),
+            DeepFeedMessage::OperationalHalt(ref m) => Some(m.timestamp),
+            DeepFeedMessage::ShortSalePriceTest(ref m) => Some(m.timestamp),
+            DeepFeedMessage::SecurityEvent(ref m) => Some(m.timestamp),
+            DeepFeedMessage::PriceLevelUpdate(ref m) => Some(m.timestamp),
+            DeepFeedMessage::TradeReport(ref m) | DeepFeedMessage::TradeBreak(ref m) => {
+                Some(m.timestamp)
+            }
+            DeepFeedMessage::OfficialPrice(ref m) => Some(m.timestamp),
+            DeepFeedMessage::AuctionInformation(ref m) => Some(m.timestamp),
+            DeepFeedMessage::Unknown { .. } => None,
+        }
+    }
+}
+
+impl<'a> Segment<'a> {
+    /// Decodes the messages of a DEEP segment.
+    pub fn deep(&self) -> impl Iterator<Item = Result<DeepFeedMessage<'a>>> {
+        self.messages()
+            .map(|message| message.and_then(DeepFeedMessage::parse))
+    }
+}
@@ -1,47 +1,132 @@
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+use reqwest::Url;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::result;
+use std::str::FromStr;
+
+#[cfg(feature = "iex-cloud")]
+use requests::TimeSeriesQuery;
 use Endpoint;
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 /// The `StocksEndpoint` enum allows for HTTP requests matching to a IEX Stocks Endpoint API.
 // TODO:(Request): Add documentation from IEX website.
 // TODO: use display_percent
 pub enum StocksEndpoint<'a> {
+    #[cfg(feature = "iex-cloud")]
+    AdvancedStats,
     Book,
     Chart {
         duration: Duration<'a>,
         params: Option<Vec<ChartParam>>,
     },
+    /// Quotes for every symbol in a sector, tag or list. Use with `MARKET`.
+    Collection {
+        collection: CollectionType,
+        name: Cow<'a, str>,
+    },
     Company,
+    /// Quotes for all supported crypto pairs. Use with `MARKET`.
+    Crypto,
     DelayedQuote,
     Dividends {
         duration: Duration<'a>,
     },
     Earnings,
     EffectiveSpread,
-    Financials,
+    /// Analyst EPS estimates. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    Estimates {
+        period: Option<Period>,
+        last: Option<NonZeroUsize>,
+    },
+    /// Combined financial statements. `last` is the number of periods, at
+    /// least one.
+    Financials {
+        period: Option<Period>,
+        last: Option<NonZeroUsize>,
+    },
+    /// Income statements. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    IncomeStatement {
+        period: Option<Period>,
+        last: Option<NonZeroUsize>,
+    },
+    /// Largest mutual fund holders. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    FundOwnership,
+    /// Largest institutional holders. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    InstitutionalOwnership,
+    /// Top insiders and their holdings. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    InsiderRoster,
+    /// Insider buys and sells. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    InsiderTransactions,
+    /// Balance sheets. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    BalanceSheet {
+        period: Option<Period>,
+        last: Option<NonZeroUsize>,
+    },
+    /// Cash flow statements. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    CashFlow {
+        period: Option<Period>,
+        last: Option<NonZeroUsize>,
+    },
     List {
         param: ListParam,
     },
     Logo,
+    /// Company news, or market-wide news for the `market` symbol. Defaults
+    /// to the last 10 articles.
     News {
-        range: Option<i32>,
+        last: Option<NewsCount>,
     },
     Ohlc,
     Peers,
     Previous,
     Price,
+    /// Analysts' price targets. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    PriceTarget,
     Quote,
+    /// Analyst buy/hold/sell ratings over time. IEX Cloud only.
+    #[cfg(feature = "iex-cloud")]
+    RecommendationTrends,
     Relevant,
+    /// Performance of each sector, based on its constituents. Use with `MARKET`.
+    SectorPerformance,
     Splits {
         duration: Duration<'a>,
     },
     Stats,
 
     // TODO: IEX Short Interest List
-    /// IEX Regulation SHO Threshold Securities List
+    /// The IEX 1.0 alias of the default `chart`, without any time series
+    /// parameters.
+    #[deprecated(
+        note = "use `Chart` for charts, or `DataEndpoint::TimeSeries` with a `TimeSeriesQuery` \
+                (`iex-cloud` feature) for time series datasets"
+    )]
     TimeSeries,
+    /// IEX Regulation SHO Threshold Securities List
     ThresholdSecurities {
         date: Option<Duration<'a>>,
     },
+    /// Earnings announced today. Use with `MARKET`.
+    TodayEarnings,
+    /// IPOs pricing today. Use with `MARKET`.
+    TodayIpos,
+    /// IPOs expected in the coming weeks. Use with `MARKET`.
+    UpcomingIpos,
     // TODO(ShortInterest): implement variant.
     VolumeByVenue,
 }
@@ -49,25 +134,34 @@ pub enum StocksEndpoint<'a> {
 impl<'a> Endpoint for StocksEndpoint<'a> {
     fn to_endpoint(self) -> String {
         match self {
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::AdvancedStats => String::from("advanced-stats"),
+
             StocksEndpoint::Book => String::from("book"),
 
-            StocksEndpoint::Chart { duration, params } => format!(
-                "chart/{}?{chart_params}",
-                duration.to_string(),
-                chart_params = match params {
-                    Some(parameters) => {
-                        let chart_params: String = parameters
-                            .iter()
-                            .map(|param| param.to_string() + "/")
-                            .collect();
-                        chart_params
-                    }
-                    None => String::from(""),
+            StocksEndpoint::Chart { duration, params } => {
+                let params: Vec<String> = params
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|param| param.to_string())
+                    .collect();
+                if params.is_empty() {
+                    format!("chart/{}", duration.to_string())
+                } else {
+                    format!("chart/{}?{}", duration.to_string(), params.join("&"))
                 }
+            }
+
+            StocksEndpoint::Collection { collection, name } => format!(
+                "collection/{}?collectionName={}",
+                collection.as_str(),
+                percent_encode(&name)
             ),
 
             StocksEndpoint::Company => String::from("company"),
 
+            StocksEndpoint::Crypto => String::from("crypto"),
+
             StocksEndpoint::DelayedQuote => String::from("delayed-quote"),
 
             StocksEndpoint::Dividends { duration } => format!("dividends/{}", duration.to_string()),
@@ -76,16 +170,50 @@ impl<'a> Endpoint for StocksEndpoint<'a> {
 
             StocksEndpoint::EffectiveSpread => String::from("effective-spread"),
 
-            StocksEndpoint::Financials => String::from("financials"),
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::Estimates { period, last } => {
+                format!("estimates{}", statement_query(period, last))
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::FundOwnership => String::from("fund-ownership"),
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::InstitutionalOwnership => String::from("institutional-ownership"),
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::InsiderRoster => String::from("insider-roster"),
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::InsiderTransactions => String::from("insider-transactions"),
+
+            StocksEndpoint::Financials { period, last } => {
+                format!("financials{}", statement_query(period, last))
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::IncomeStatement { period, last } => {
+                format!("income{}", statement_query(period, last))
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::BalanceSheet { period, last } => {
+                format!("balance-sheet{}", statement_query(period, last))
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::CashFlow { period, last } => {
+                format!("cash-flow{}", statement_query(period, last))
+            }
 
             StocksEndpoint::List { param } => format!("list/{}", param.to_string()),
 
             StocksEndpoint::Logo => String::from("logo"),
 
-            StocksEndpoint::News { range } => format!(
-                "news/last/{}",
-                range.map(|r| r.to_string()).unwrap_or("".to_string()),
-            ),
+            StocksEndpoint::News { last } => match last {
+                Some(last) => format!("news/last/{}", last.get()),
+                None => String::from("news"),
+            },
 
             StocksEndpoint::Ohlc => String::from("ohlc"),
 
@@ -95,27 +223,381 @@ impl<'a> Endpoint for StocksEndpoint<'a> {
 
             StocksEndpoint::Price => String::from("price"),
 
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::PriceTarget => String::from("price-target"),
+
             StocksEndpoint::Quote => String::from("quote"),
 
+            #[cfg(feature = "iex-cloud")]
+            StocksEndpoint::RecommendationTrends => String::from("recommendation-trends"),
+
             StocksEndpoint::Relevant => String::from("relevant"),
 
+            StocksEndpoint::SectorPerformance => String::from("sector-performance"),
+
             StocksEndpoint::Stats => String::from("stats"),
 
             StocksEndpoint::Splits { duration } => format!("splits/{}", duration.to_string()),
 
+            #[allow(deprecated)]
             StocksEndpoint::TimeSeries => String::from("time-series"),
 
-            StocksEndpoint::ThresholdSecurities { date } => format!(
-                "threshold-securities/{}",
-                date.unwrap_or(Duration::None).to_string(),
-            ),
+            StocksEndpoint::ThresholdSecurities { date } => match date {
+                Some(Duration::None) | None => String::from("threshold-securities"),
+                Some(date) => format!("threshold-securities/{}", date.to_string()),
+            },
+
+            StocksEndpoint::TodayEarnings => String::from("today-earnings"),
+
+            StocksEndpoint::TodayIpos => String::from("today-ipos"),
+
+            StocksEndpoint::UpcomingIpos => String::from("upcoming-ipos"),
 
             StocksEndpoint::VolumeByVenue => String::from("volume-by-venue"),
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+fn statement_query(period: Option<Period>, last: Option<NonZeroUsize>) -> String {
+    match (period, last) {
+        (Some(period), Some(last)) => format!("?period={}&last={}", period.as_str(), last),
+        (Some(period), None) => format!("?period={}", period.as_str()),
+        (None, Some(last)) => format!("?last={}", last),
+        (None, None) => String::new(),
+    }
+}
+
+/// Percent-encodes everything but unreserved URL characters.
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Reverses `percent_encode`, also accepting `+` for a space. Returns `None`
+/// for malformed escapes or invalid UTF-8.
+fn percent_decode(value: &str) -> Option<Cow<'_, str>> {
+    if !value.contains('%') && !value.contains('+') {
+        return Some(Cow::Borrowed(value));
+    }
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex = [input.next()?, input.next()?];
+                let hex = ::std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok().map(Cow::Owned)
+}
+
+impl<'a> StocksEndpoint<'a> {
+    /// Parses a stocks URL or path, such as `/stock/aapl/chart/1m?chartLast=5`
+    /// or `https://api.iextrading.com/1.0/stock/aapl/book`, into its symbol
+    /// and endpoint.
+    pub fn from_path(
+        path: &'a str,
+    ) -> result::Result<(&'a str, StocksEndpoint<'a>), ParseEndpointError> {
+        let end = path.find('?').unwrap_or(path.len());
+        let start = if path.starts_with("stock/") {
+            0
+        } else {
+            match path[..end].find("/stock/") {
+                Some(i) => i + 1,
+                None => return Err(ParseEndpointError::new(path)),
+            }
+        };
+        let rest = &path[start + "stock/".len()..];
+        match rest.find('/') {
+            Some(i) if i > 0 => Ok((&rest[..i], StocksEndpoint::parse(&rest[i + 1..])?)),
+            _ => Err(ParseEndpointError::new(path)),
+        }
+    }
+
+    /// Parses the output of `to_endpoint`, such as `chart/1m?chartLast=5`,
+    /// back into an endpoint.
+    pub fn parse(endpoint: &'a str) -> result::Result<StocksEndpoint<'a>, ParseEndpointError> {
+        let err = || ParseEndpointError::new(endpoint);
+        let (path, query) = match endpoint.find('?') {
+            Some(i) => (&endpoint[..i], Some(&endpoint[i + 1..])),
+            None => (endpoint, None),
+        };
+        let (name, rest) = match path.find('/') {
+            Some(i) => (&path[..i], Some(&path[i + 1..])),
+            None => (path, None),
+        };
+        type Statement = (Option<Period>, Option<NonZeroUsize>);
+        let statement = || -> result::Result<Statement, ParseEndpointError> {
+            let mut period = None;
+            let mut last = None;
+            for param in query.unwrap_or("").split('&').filter(|param| !param.is_empty()) {
+                match param.split_once('=') {
+                    Some(("period", value)) => period = Some(value.parse()?),
+                    Some(("last", value)) => last = Some(value.parse().map_err(|_| err())?),
+                    _ => return Err(err()),
+                }
+            }
+            Ok((period, last))
+        };
+        let takes_query = matches!(
+            name,
+            "chart" | "collection" | "financials" | "income" | "balance-sheet" | "cash-flow"
+                | "estimates"
+        );
+        if query.is_some() && !takes_query {
+            return Err(err());
+        }
+
+        Ok(match (name, rest) {
+            #[cfg(feature = "iex-cloud")]
+            ("advanced-stats", None) => StocksEndpoint::AdvancedStats,
+
+            ("book", None) => StocksEndpoint::Book,
+
+            ("chart", rest) => StocksEndpoint::Chart {
+                duration: Duration::parse(rest.unwrap_or(""))?,
+                params: match query {
+                    Some(query) => Some(
+                        query
+                            .split('&')
+                            .filter(|param| !param.is_empty())
+                            .map(str::parse)
+                            .collect::<result::Result<_, _>>()?,
+                    ),
+                    None => None,
+                },
+            },
+
+            ("collection", Some(collection)) => StocksEndpoint::Collection {
+                collection: collection.parse()?,
+                name: match query.and_then(|query| query.strip_prefix("collectionName=")) {
+                    Some(name) if !name.contains('&') => percent_decode(name).ok_or_else(err)?,
+                    _ => return Err(err()),
+                },
+            },
+
+            ("company", None) => StocksEndpoint::Company,
+
+            ("crypto", None) => StocksEndpoint::Crypto,
+
+            ("delayed-quote", None) => StocksEndpoint::DelayedQuote,
+
+            ("dividends", rest) => StocksEndpoint::Dividends {
+                duration: Duration::parse(rest.unwrap_or(""))?,
+            },
+
+            ("earnings", None) => StocksEndpoint::Earnings,
+
+            ("effective-spread", None) => StocksEndpoint::EffectiveSpread,
+
+            #[cfg(feature = "iex-cloud")]
+            ("estimates", None) => {
+                let (period, last) = statement()?;
+                StocksEndpoint::Estimates { period, last }
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            ("fund-ownership", None) => StocksEndpoint::FundOwnership,
+
+            #[cfg(feature = "iex-cloud")]
+            ("institutional-ownership", None) => StocksEndpoint::InstitutionalOwnership,
+
+            #[cfg(feature = "iex-cloud")]
+            ("insider-roster", None) => StocksEndpoint::InsiderRoster,
+
+            #[cfg(feature = "iex-cloud")]
+            ("insider-transactions", None) => StocksEndpoint::InsiderTransactions,
+
+            ("financials", None) => {
+                let (period, last) = statement()?;
+                StocksEndpoint::Financials { period, last }
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            ("income", None) => {
+                let (period, last) = statement()?;
+                StocksEndpoint::IncomeStatement { period, last }
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            ("balance-sheet", None) => {
+                let (period, last) = statement()?;
+                StocksEndpoint::BalanceSheet { period, last }
+            }
+
+            #[cfg(feature = "iex-cloud")]
+            ("cash-flow", None) => {
+                let (period, last) = statement()?;
+                StocksEndpoint::CashFlow { period, last }
+            }
+
+            ("list", Some(param)) => StocksEndpoint::List {
+                param: param.parse()?,
+            },
+
+            ("logo", None) => StocksEndpoint::Logo,
+
+            ("news", None) | ("news", Some("last")) | ("news", Some("last/")) => {
+                StocksEndpoint::News { last: None }
+            }
+
+            ("news", Some(rest)) => match rest.strip_prefix("last/") {
+                Some(last) => StocksEndpoint::News {
+                    last: Some(last.parse()?),
+                },
+                None => return Err(err()),
+            },
+
+            ("ohlc", None) => StocksEndpoint::Ohlc,
+
+            ("peers", None) => StocksEndpoint::Peers,
+
+            ("previous", None) => StocksEndpoint::Previous,
+
+            ("price", None) => StocksEndpoint::Price,
+
+            #[cfg(feature = "iex-cloud")]
+            ("price-target", None) => StocksEndpoint::PriceTarget,
+
+            ("quote", None) => StocksEndpoint::Quote,
+
+            #[cfg(feature = "iex-cloud")]
+            ("recommendation-trends", None) => StocksEndpoint::RecommendationTrends,
+
+            ("relevant", None) => StocksEndpoint::Relevant,
+
+            ("sector-performance", None) => StocksEndpoint::SectorPerformance,
+
+            ("stats", None) => StocksEndpoint::Stats,
+
+            ("splits", rest) => StocksEndpoint::Splits {
+                duration: Duration::parse(rest.unwrap_or(""))?,
+            },
+
+            #[allow(deprecated)]
+            ("time-series", None) => StocksEndpoint::TimeSeries,
+
+            ("threshold-securities", rest) => StocksEndpoint::ThresholdSecurities {
+                date: match Duration::parse(rest.unwrap_or(""))? {
+                    Duration::None => None,
+                    date => Some(date),
+                },
+            },
+
+            ("today-earnings", None) => StocksEndpoint::TodayEarnings,
+
+            ("today-ipos", None) => StocksEndpoint::TodayIpos,
+
+            ("upcoming-ipos", None) => StocksEndpoint::UpcomingIpos,
+
+            ("volume-by-venue", None) => StocksEndpoint::VolumeByVenue,
+
+            _ => return Err(err()),
+        })
+    }
+}
+
+/// `StocksEndpointBuf` is an owned `StocksEndpoint`, for endpoints kept
+/// apart from the string they were parsed from, such as those of config
+/// files. It serializes like `StocksEndpoint`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StocksEndpointBuf(String);
+
+impl StocksEndpointBuf {
+    pub fn endpoint(&self) -> StocksEndpoint<'_> {
+        StocksEndpoint::parse(&self.0).expect("StocksEndpointBuf holds a parsed endpoint")
+    }
+}
+
+impl FromStr for StocksEndpointBuf {
+    type Err = ParseEndpointError;
+
+    fn from_str(endpoint: &str) -> result::Result<StocksEndpointBuf, ParseEndpointError> {
+        // The output of `to_endpoint` always parses again.
+        Ok(StocksEndpointBuf(StocksEndpoint::parse(endpoint)?.to_endpoint()))
+    }
+}
+
+impl fmt::Display for StocksEndpointBuf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// `StocksPath` is an owned stocks URL or path, parsed into its symbol and
+/// endpoint, for keeping parsed requests such as those of a request log.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StocksPath {
+    symbol: String,
+    endpoint: StocksEndpointBuf,
+}
+
+impl StocksPath {
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn endpoint(&self) -> StocksEndpoint<'_> {
+        self.endpoint.endpoint()
+    }
+}
+
+impl FromStr for StocksPath {
+    type Err = ParseEndpointError;
+
+    fn from_str(path: &str) -> result::Result<StocksPath, ParseEndpointError> {
+        let (symbol, endpoint) = StocksEndpoint::from_path(path)?;
+        Ok(StocksPath {
+            symbol: symbol.to_string(),
+            endpoint: StocksEndpointBuf(endpoint.to_endpoint()),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a Url> for StocksPath {
+    type Error = ParseEndpointError;
+
+    fn try_from(url: &'a Url) -> result::Result<StocksPath, ParseEndpointError> {
+        url.as_str().parse()
+    }
+}
+
+/// `ParseEndpointError` is returned when a string doesn't match the format
+/// of any endpoint or parameter.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseEndpointError {
+    pub input: String,
+}
+
+impl ParseEndpointError {
+    fn new(input: &str) -> ParseEndpointError {
+        ParseEndpointError {
+            input: input.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseEndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unrecognised IEX endpoint: {:?}", self.input)
+    }
+}
+
+impl error::Error for ParseEndpointError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Duration<'a> {
     FiveYears,
     TwoYears,
@@ -148,13 +630,36 @@ impl<'a> ToString for Duration<'a> {
     }
 }
 
+impl<'a> Duration<'a> {
+    /// Parses a duration as written by `to_string`; the empty string is
+    /// `Duration::None`.
+    pub fn parse(duration: &'a str) -> result::Result<Duration<'a>, ParseEndpointError> {
+        Ok(match duration {
+            "5y" => Duration::FiveYears,
+            "2y" => Duration::TwoYears,
+            "1y" => Duration::OneYear,
+            "ytd" => Duration::YearToDate,
+            "6m" => Duration::SixMonths,
+            "3m" => Duration::ThreeMonths,
+            "1m" => Duration::OneMonth,
+            "1d" => Duration::OneDay,
+            "dynamic" => Duration::Dynamic,
+            "" => Duration::None,
+            _ => match duration.strip_prefix("date/") {
+                Some(date) if !date.is_empty() && !date.contains('/') => Duration::Date(date),
+                _ => return Err(ParseEndpointError::new(duration)),
+            },
+        })
+    }
+}
+
 impl<'a> Default for Duration<'a> {
     fn default() -> Duration<'a> {
         Duration::OneMonth
     }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ListParam {
     MostActive,
     Gainers,
@@ -175,7 +680,116 @@ impl ToString for ListParam {
     }
 }
 
-#[derive(PartialEq, Eq)]
+impl FromStr for ListParam {
+    type Err = ParseEndpointError;
+
+    fn from_str(param: &str) -> result::Result<ListParam, ParseEndpointError> {
+        match param {
+            "mostactive" => Ok(ListParam::MostActive),
+            "gainers" => Ok(ListParam::Gainers),
+            "losers" => Ok(ListParam::Losers),
+            "iexvolume" => Ok(ListParam::IexVolume),
+            "iexpercent" => Ok(ListParam::IexPercent),
+            _ => Err(ParseEndpointError::new(param)),
+        }
+    }
+}
+
+/// `CollectionType` is the kind of grouping a collection is named from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectionType {
+    Sector,
+    Tag,
+    List,
+}
+
+impl CollectionType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CollectionType::Sector => "sector",
+            CollectionType::Tag => "tag",
+            CollectionType::List => "list",
+        }
+    }
+}
+
+impl FromStr for CollectionType {
+    type Err = ParseEndpointError;
+
+    fn from_str(collection: &str) -> result::Result<CollectionType, ParseEndpointError> {
+        match collection {
+            "sector" => Ok(CollectionType::Sector),
+            "tag" => Ok(CollectionType::Tag),
+            "list" => Ok(CollectionType::List),
+            _ => Err(ParseEndpointError::new(collection)),
+        }
+    }
+}
+
+/// `Period` selects annual or quarterly financial statements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Annual,
+    Quarter,
+}
+
+impl Period {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Period::Annual => "annual",
+            Period::Quarter => "quarter",
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = ParseEndpointError;
+
+    fn from_str(period: &str) -> result::Result<Period, ParseEndpointError> {
+        match period {
+            "annual" => Ok(Period::Annual),
+            "quarter" => Ok(Period::Quarter),
+            _ => Err(ParseEndpointError::new(period)),
+        }
+    }
+}
+
+/// `NewsCount` is the number of articles to fetch from the news endpoint,
+/// which IEX limits to between 1 and 50.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NewsCount(u8);
+
+impl NewsCount {
+    /// The largest count IEX accepts.
+    pub const MAX: u8 = 50;
+
+    /// Returns `None` unless `count` is between 1 and `NewsCount::MAX`.
+    pub fn new(count: u8) -> Option<NewsCount> {
+        if (1..=NewsCount::MAX).contains(&count) {
+            Some(NewsCount(count))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl FromStr for NewsCount {
+    type Err = ParseEndpointError;
+
+    fn from_str(count: &str) -> result::Result<NewsCount, ParseEndpointError> {
+        count
+            .parse()
+            .ok()
+            .and_then(NewsCount::new)
+            .ok_or_else(|| ParseEndpointError::new(count))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ChartParam {
     /// If true, 1d chart will reset at midnight instead of the default behavior of 9:30am ET.
     Reset(bool),
@@ -201,6 +815,196 @@ impl ToString for ChartParam {
     }
 }
 
+impl FromStr for ChartParam {
+    type Err = ParseEndpointError;
+
+    fn from_str(param: &str) -> result::Result<ChartParam, ParseEndpointError> {
+        let err = || ParseEndpointError::new(param);
+        let (key, value) = param.split_once('=').ok_or_else(err)?;
+        match key {
+            "chartReset" => value.parse().map(ChartParam::Reset).map_err(|_| err()),
+            "chartSimplify" => value.parse().map(ChartParam::Simplify).map_err(|_| err()),
+            "chartInterval" => value.parse().map(ChartParam::Interval).map_err(|_| err()),
+            "changeFromClose" => value
+                .parse()
+                .map(ChartParam::ChangeFromClose)
+                .map_err(|_| err()),
+            "chartLast" => value.parse().map(ChartParam::Last).map_err(|_| err()),
+            _ => Err(err()),
+        }
+    }
+}
+
+// The request model serializes to the strings IEX uses in its URLs, e.g.
+// "chart/1y?chartLast=5", "1y", "gainers" and "chartLast=5", so config files
+// read the same as the API documentation.
+
+impl<'a> Serialize for StocksEndpoint<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.clone().to_endpoint())
+    }
+}
+
+// Endpoints and durations borrow from the input, so they only deserialize
+// from strings without escapes. `StocksEndpointBuf` deserializes from any.
+impl<'de: 'a, 'a> Deserialize<'de> for StocksEndpoint<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        let endpoint = <&'de str>::deserialize(deserializer)?;
+        StocksEndpoint::parse(endpoint).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for StocksEndpointBuf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for StocksEndpointBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<'a> Serialize for Duration<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Duration<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        let duration = <&'de str>::deserialize(deserializer)?;
+        Duration::parse(duration).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for ListParam {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ListParam {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for ChartParam {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ChartParam {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// The `DataEndpoint` enum covers IEX Cloud's non-stock data: FX rates,
+/// market data points and economic time series. Currency pairs are written
+/// as `USDCAD`.
+#[cfg(feature = "iex-cloud")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataEndpoint<'a> {
+    /// Latest rates for each currency pair.
+    FxLatest { symbols: Vec<Cow<'a, str>> },
+    /// Converts `amount` (1 if unset) for each currency pair. The amount is
+    /// kept as text so it is passed to IEX exactly as given.
+    FxConvert {
+        symbols: Vec<Cow<'a, str>>,
+        amount: Option<Cow<'a, str>>,
+    },
+    /// Daily rates for each currency pair, optionally between `from` and `to`
+    /// (`YYYY-MM-DD`) or for the `last` N days.
+    FxHistorical {
+        symbols: Vec<Cow<'a, str>>,
+        from: Option<Cow<'a, str>>,
+        to: Option<Cow<'a, str>>,
+        last: Option<usize>,
+    },
+    /// The latest value of a market-wide data point, such as `DGS10` (the
+    /// 10 year treasury rate) or `DCOILWTICO` (WTI crude oil).
+    MarketDataPoint { key: Cow<'a, str> },
+    /// History of a treasury rate such as `DGS10`.
+    Treasury {
+        key: Cow<'a, str>,
+        last: Option<usize>,
+    },
+    /// Rows of a time series dataset such as `REPORTED_FINANCIALS`.
+    TimeSeries(TimeSeriesQuery<'a>),
+}
+
+#[cfg(feature = "iex-cloud")]
+impl<'a> Endpoint for DataEndpoint<'a> {
+    fn to_endpoint(self) -> String {
+        match self {
+            DataEndpoint::FxLatest { symbols } => {
+                format!("fx/latest?symbols={}", fx_symbols(&symbols))
+            }
+
+            DataEndpoint::FxConvert { symbols, amount } => {
+                let mut endpoint = format!("fx/convert?symbols={}", fx_symbols(&symbols));
+                if let Some(amount) = amount {
+                    endpoint.push_str(&format!("&amount={}", percent_encode(&amount)));
+                }
+                endpoint
+            }
+
+            DataEndpoint::FxHistorical {
+                symbols,
+                from,
+                to,
+                last,
+            } => {
+                let mut endpoint = format!("fx/historical?symbols={}", fx_symbols(&symbols));
+                if let Some(from) = from {
+                    endpoint.push_str(&format!("&from={}", percent_encode(&from)));
+                }
+                if let Some(to) = to {
+                    endpoint.push_str(&format!("&to={}", percent_encode(&to)));
+                }
+                if let Some(last) = last {
+                    endpoint.push_str(&format!("&last={}", last));
+                }
+                endpoint
+            }
+
+            DataEndpoint::MarketDataPoint { key } => {
+                format!("data-points/market/{}", percent_encode(&key))
+            }
+
+            DataEndpoint::Treasury { key, last } => {
+                let endpoint = format!("time-series/treasury/{}", percent_encode(&key));
+                match last {
+                    Some(last) => format!("{}?last={}", endpoint, last),
+                    None => endpoint,
+                }
+            }
+
+            DataEndpoint::TimeSeries(query) => query.to_endpoint(),
+        }
+    }
+}
+
+/// Joins currency pairs for a `symbols` parameter, encoding each one.
+#[cfg(feature = "iex-cloud")]
+fn fx_symbols(symbols: &[Cow<str>]) -> String {
+    symbols
+        .iter()
+        .map(|symbol| percent_encode(symbol))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 // pub enum ReferenceEndpoint<'a> {
 //     Symbols,
 //     CorporateActions { date: Option<&'a str> },
@@ -0,0 +1,268 @@
+//! IEX HIST: daily captures of the TOPS and DEEP feeds, and a local archive
+//! to download them into.
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{ByteRangeSpec, Range};
+use serde_json::{self, Map, Value};
+
+use de;
+use {Client, Result};
+
+string_enum! {
+    /// The market data feed captured in a HIST file.
+    pub enum HistFeed {
+        Tops => "TOPS",
+        Deep => "DEEP",
+    }
+}
+
+/// One downloadable HIST file: a gzipped pcap capture of a day's feed.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistEntry {
+    pub link: String,
+    /// The trading day, as `YYYYMMDD`.
+    pub date: String,
+    pub feed: HistFeed,
+    /// The feed's protocol version, e.g. `1.6` for TOPS.
+    pub version: String,
+    /// The transport protocol, e.g. `IEXTP1`.
+    pub protocol: String,
+    /// The file size in bytes.
+    #[serde(deserialize_with = "de::number")]
+    pub size: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl HistEntry {
+    /// Returns the name IEX gives the file, e.g.
+    /// `20180102_IEXTP1_DEEP1.0.pcap.gz`. The name is built from the listing,
+    /// so fields that aren't plain names are an `InvalidHistValue`, rather
+    /// than a path that could lead out of an archive.
+    pub fn file_name(&self) -> Result<String> {
+        Ok(format!(
+            "{}_{}_{}{}.pcap.gz",
+            checked_date(&self.date)?,
+            checked_name("protocol", &self.protocol)?,
+            checked_name("feed", self.feed.as_str())?,
+            checked_name("version", &self.version)?
+        ))
+    }
+}
+
+/// `InvalidHistValue` is returned for a HIST date or listing field that
+/// doesn't have the expected form.
+#[derive(Debug)]
+pub struct InvalidHistValue {
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidHistValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid HIST {}: {:?}", self.field, self.value)
+    }
+}
+
+impl error::Error for InvalidHistValue {}
+
+/// Checks that `date` is a `YYYYMMDD` date.
+fn checked_date(date: &str) -> Result<&str> {
+    if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) {
+        Ok(date)
+    } else {
+        Err(InvalidHistValue {
+            field: "date",
+            value: date.to_string(),
+        }.into())
+    }
+}
+
+/// Checks that `value` is made of letters, digits, `.`, `_` and `-` only.
+fn checked_name<'a>(field: &'static str, value: &'a str) -> Result<&'a str> {
+    let plain = |b: u8| b.is_ascii_alphanumeric() || b == b'.' || b == b'_' || b == b'-';
+    if !value.is_empty() && value.bytes().all(plain) && value != "." && value != ".." {
+        Ok(value)
+    } else {
+        Err(InvalidHistValue {
+            field,
+            value: value.to_string(),
+        }.into())
+    }
+}
+
+/// `UnexpectedStatus` is returned when a download gets an HTTP error.
+#[derive(Debug)]
+pub struct UnexpectedStatus {
+    pub url: String,
+    pub status: u16,
+}
+
+impl fmt::Display for UnexpectedStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HTTP status {} from {}", self.status, self.url)
+    }
+}
+
+impl error::Error for UnexpectedStatus {}
+
+/// `SizeMismatch` is returned when a download ends at a different size than
+/// listed. A short download is kept, to be resumed by the next attempt.
+#[derive(Debug)]
+pub struct SizeMismatch {
+    pub file: String,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} bytes, expected {}",
+            self.file, self.actual, self.expected
+        )
+    }
+}
+
+impl error::Error for SizeMismatch {}
+
+/// The catalogue's file name within an archive directory.
+const CATALOGUE: &str = "catalogue.json";
+
+/// `HistArchive` is a directory of downloaded HIST files, with a catalogue of
+/// the entries downloaded so far. Downloads go to a `.part` file first, so
+/// an interrupted download resumes where it stopped.
+pub struct HistArchive {
+    dir: PathBuf,
+    http: ::reqwest::Client,
+    catalogue: BTreeMap<String, HistEntry>,
+}
+
+impl HistArchive {
+    /// Opens the archive in `dir`, creating the directory if needed.
+    pub fn open<P>(dir: P) -> Result<HistArchive>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let catalogue = match File::open(dir.join(CATALOGUE)) {
+            Ok(file) => serde_json::from_reader(io::BufReader::new(file))?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(HistArchive {
+            dir,
+            http: ::reqwest::Client::new(),
+            catalogue,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the downloaded entries, by file name.
+    pub fn catalogue(&self) -> &BTreeMap<String, HistEntry> {
+        &self.catalogue
+    }
+
+    /// Returns where `entry` is, or will be, stored.
+    pub fn path(&self, entry: &HistEntry) -> Result<PathBuf> {
+        Ok(self.dir.join(entry.file_name()?))
+    }
+
+    /// Returns whether `entry` has been downloaded.
+    pub fn contains(&self, entry: &HistEntry) -> bool {
+        entry.file_name().is_ok_and(|name| {
+            self.catalogue.contains_key(&name)
+                && fs::metadata(self.dir.join(&name)).is_ok_and(|file| file.len() == entry.size)
+        })
+    }
+
+    /// Downloads `entry` unless it already has been, resuming a partial
+    /// download, and returns its path.
+    pub fn download(&mut self, entry: &HistEntry) -> Result<PathBuf> {
+        let name = entry.file_name()?;
+        let path = self.dir.join(&name);
+        if self.contains(entry) {
+            return Ok(path);
+        }
+
+        let part = self.dir.join(format!("{}.part", name));
+        let mut offset = fs::metadata(&part).map(|file| file.len()).unwrap_or(0);
+        if offset > entry.size {
+            fs::remove_file(&part)?;
+            offset = 0;
+        }
+        if offset < entry.size {
+            let mut request = self.http.get(entry.link.as_str());
+            if offset > 0 {
+                request.header(Range::Bytes(vec![ByteRangeSpec::AllFrom(offset)]));
+            }
+            let mut response = request.send()?;
+            // Servers that ignore the range send the whole file again.
+            let resume = match response.status().as_u16() {
+                206 => true,
+                200 => false,
+                status => {
+                    return Err(UnexpectedStatus {
+                        url: entry.link.clone(),
+                        status,
+                    }.into())
+                }
+            };
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resume)
+                .truncate(!resume)
+                .open(&part)?;
+            io::copy(&mut response, &mut file)?;
+        }
+
+        let actual = fs::metadata(&part)?.len();
+        if actual != entry.size {
+            if actual > entry.size {
+                fs::remove_file(&part)?;
+            }
+            return Err(SizeMismatch {
+                file: name,
+                expected: entry.size,
+                actual,
+            }.into());
+        }
+        fs::rename(&part, &path)?;
+        self.catalogue.insert(name, entry.clone());
+        self.save()?;
+        Ok(path)
+    }
+
+    /// Writes the catalogue, replacing the old one only once it is complete.
+    fn save(&self) -> Result<()> {
+        let tmp = self.dir.join(format!("{}.tmp", CATALOGUE));
+        serde_json::to_writer_pretty(File::create(&tmp)?, &self.catalogue)?;
+        fs::rename(&tmp, self.dir.join(CATALOGUE))?;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Lists the HIST files of every available day, by date (`YYYYMMDD`).
+    pub fn hist(&self) -> Result<BTreeMap<String, Vec<HistEntry>>> {
+        Ok(::reqwest::get(&self.url("hist"))?.json()?)
+    }
+
+    /// Lists the HIST files of `date` (`YYYYMMDD`).
+    pub fn hist_date(&self, date: &str) -> Result<Vec<HistEntry>> {
+        let date = checked_date(date)?;
+        Ok(::reqwest::get(&self.url(&format!("hist?date={}", date)))?.json()?)
+    }
+}
@@ -0,0 +1,624 @@
+//! IEX Transport Protocol (IEX-TP) segments, read from pcap and pcap-ng
+//! captures such as HIST files.
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::Range;
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+use types::Money;
+use Result;
+
+/// The message protocol id of TOPS segments.
+pub const TOPS_PROTOCOL: u16 = 0x8003;
+/// The message protocol id of DEEP segments.
+pub const DEEP_PROTOCOL: u16 = 0x8004;
+
+const SEGMENT_HEADER_LEN: usize = 40;
+/// Larger packets or blocks mean a corrupt capture, not a big one.
+const MAX_RECORD_LEN: usize = 1 << 20;
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const PCAPNG_INTERFACE: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+
+/// `DecodeError` is returned for captures and messages that are malformed or
+/// in an unsupported format.
+#[derive(Debug)]
+pub struct DecodeError {
+    pub reason: String,
+}
+
+impl DecodeError {
+    pub(crate) fn new<S: Into<String>>(reason: S) -> DecodeError {
+        DecodeError {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot decode IEX capture: {}", self.reason)
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// Reads the little-endian fields of a binary message in order, borrowing
+/// text from the message.
+pub(crate) struct Fields<'a> {
+    data: &'a [u8],
+    what: &'static str,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn new(data: &'a [u8], what: &'static str) -> Fields<'a> {
+        Fields { data, what }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(DecodeError::new(format!("{} is truncated", self.what)).into());
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16> {
+        let mut bytes = [0; 2];
+        bytes.copy_from_slice(self.bytes(2)?);
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads a price: a signed integer with four implied decimals.
+    pub(crate) fn price(&mut self) -> Result<Money> {
+        let raw = self.u64()? as i64;
+        #[cfg(not(feature = "decimal"))]
+        let price = raw as f64 / 10_000.0;
+        #[cfg(feature = "decimal")]
+        let price = ::rust_decimal::Decimal::new(raw, 4);
+        Ok(price)
+    }
+
+    /// Reads `len` bytes of space-padded ASCII, without the padding.
+    pub(crate) fn text(&mut self, len: usize) -> Result<&'a str> {
+        let what = self.what;
+        let text = ::std::str::from_utf8(self.bytes(len)?)
+            .map_err(|_| DecodeError::new(format!("{} has non-ASCII text", what)))?;
+        Ok(text.trim_end_matches(' '))
+    }
+
+    /// Reads an 8-byte symbol.
+    pub(crate) fn symbol(&mut self) -> Result<&'a str> {
+        self.text(8)
+    }
+}
+
+/// `Segment` is one IEX-TP segment: a header and a block of messages of one
+/// message protocol, such as TOPS.
+#[derive(Clone, Debug)]
+pub struct Segment<'a> {
+    pub version: u8,
+    /// The message protocol id, e.g. `TOPS_PROTOCOL`.
+    pub protocol: u16,
+    pub channel: u32,
+    pub session: u32,
+    /// The number of messages; zero for a heartbeat.
+    pub message_count: u16,
+    /// The byte offset of the payload within the session's stream.
+    pub stream_offset: u64,
+    /// The sequence number of the first message.
+    pub first_seq: u64,
+    /// When the segment was sent, in nanoseconds since the epoch.
+    pub send_time: u64,
+    payload: &'a [u8],
+}
+
+impl<'a> Segment<'a> {
+    /// Parses a segment from a UDP payload.
+    pub fn parse(data: &'a [u8]) -> Result<Segment<'a>> {
+        let mut fields = Fields::new(data, "IEX-TP segment");
+        let version = fields.u8()?;
+        if version != 1 {
+            return Err(DecodeError::new(format!("IEX-TP version {}", version)).into());
+        }
+        fields.u8()?;
+        let protocol = fields.u16()?;
+        let channel = fields.u32()?;
+        let session = fields.u32()?;
+        let payload_len = fields.u16()?;
+        let message_count = fields.u16()?;
+        Ok(Segment {
+            version,
+            protocol,
+            channel,
+            session,
+            message_count,
+            stream_offset: fields.u64()?,
+            first_seq: fields.u64()?,
+            send_time: fields.u64()?,
+            payload: fields.bytes(payload_len as usize)?,
+        })
+    }
+
+    /// Returns the messages, undecoded.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns the undecoded messages one by one. The `n`th has sequence
+    /// number `first_seq + n`.
+    pub fn messages(&self) -> Messages<'a> {
+        Messages {
+            fields: Fields::new(self.payload, "IEX-TP message block"),
+        }
+    }
+}
+
+/// `Messages` iterates over the messages of a `Segment`.
+pub struct Messages<'a> {
+    fields: Fields<'a>,
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = Result<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Result<&'a [u8]>> {
+        if self.fields.data.is_empty() {
+            return None;
+        }
+        let message = self
+            .fields
+            .u16()
+            .and_then(|len| self.fields.bytes(len as usize));
+        if message.is_err() {
+            self.fields.data = &[];
+        }
+        Some(message)
+    }
+}
+
+/// `Packet` is one captured link-layer frame.
+#[derive(Clone, Debug)]
+pub struct Packet<'a> {
+    /// When the packet was captured, in nanoseconds since the epoch.
+    pub timestamp: u64,
+    /// The pcap link type, e.g. 1 for Ethernet.
+    pub link_type: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    /// Returns the UDP payload, if this is an unfragmented UDP packet over
+    /// IPv4 or IPv6.
+    pub fn udp_payload(&self) -> Option<&'a [u8]> {
+        udp_payload(self.link_type, self.data).map(|range| &self.data[range])
+    }
+}
+
+/// A packet's place in `Capture::buf`.
+struct Frame {
+    timestamp: u64,
+    link_type: u32,
+    data: Range<usize>,
+}
+
+enum Format {
+    Pcap {
+        big_endian: bool,
+        nanos: bool,
+        link_type: u32,
+    },
+    PcapNg {
+        big_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Interface {
+    link_type: u32,
+    /// `if_tsresol`: timestamps count 10^-n seconds, or 2^-n if the high bit
+    /// is set.
+    resolution: u8,
+}
+
+impl Interface {
+    fn nanos(&self, timestamp: u64) -> u64 {
+        let exponent = u32::from(self.resolution & 0x7f);
+        if self.resolution & 0x80 != 0 {
+            ((u128::from(timestamp) * 1_000_000_000) >> exponent.min(127)) as u64
+        } else if exponent <= 9 {
+            timestamp.saturating_mul(10u64.pow(9 - exponent))
+        } else {
+            timestamp / 10u64.saturating_pow(exponent - 9)
+        }
+    }
+}
+
+/// `Capture` reads packets from a pcap or pcap-ng capture, one at a time.
+/// Each packet, and everything decoded from it, borrows the capture's buffer
+/// until the next read, so reading never copies or allocates per message.
+pub struct Capture<R> {
+    reader: R,
+    format: Format,
+    buf: Vec<u8>,
+}
+
+impl Capture<Box<dyn Read>> {
+    /// Opens a capture file, gzipped or not.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Capture::from_reader(File::open(path)?)
+    }
+
+    /// Reads a capture from `reader`, decompressing it if it is gzipped.
+    pub fn from_reader<R: Read + 'static>(reader: R) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let gzipped = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn Read> = if gzipped {
+            Box::new(MultiGzDecoder::new(reader))
+        } else {
+            Box::new(reader)
+        };
+        Capture::new(reader)
+    }
+}
+
+impl<R: Read> Capture<R> {
+    /// Reads an uncompressed capture from `reader`, telling pcap from
+    /// pcap-ng by its header.
+    pub fn new(mut reader: R) -> Result<Capture<R>> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let format = if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER {
+            let big_endian = read_section_header(&mut reader)?;
+            Format::PcapNg {
+                big_endian,
+                interfaces: Vec::new(),
+            }
+        } else {
+            let (big_endian, nanos) = match u32::from_le_bytes(magic) {
+                0xA1B2_C3D4 => (false, false),
+                0xD4C3_B2A1 => (true, false),
+                0xA1B2_3C4D => (false, true),
+                0x4D3C_B2A1 => (true, true),
+                magic => {
+                    let reason = format!("not a pcap or pcap-ng file (magic {:#010x})", magic);
+                    return Err(DecodeError::new(reason).into());
+                }
+            };
+            let mut header = [0; 20];
+            reader.read_exact(&mut header)?;
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type: read_u32(&header[16..20], big_endian),
+            }
+        };
+        Ok(Capture {
+            reader,
+            format,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Reads the next packet, or `None` at the end of the capture.
+    pub fn next_packet(&mut self) -> Result<Option<Packet<'_>>> {
+        Ok(self.read_frame()?.map(move |frame| Packet {
+            timestamp: frame.timestamp,
+            link_type: frame.link_type,
+            data: &self.buf[frame.data],
+        }))
+    }
+
+    /// Reads the next IEX-TP segment, skipping packets that don't carry one,
+    /// or returns `None` at the end of the capture.
+    pub fn next_segment(&mut self) -> Result<Option<Segment<'_>>> {
+        let range = loop {
+            let frame = match self.read_frame()? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            let start = frame.data.start;
+            if let Some(payload) = udp_payload(frame.link_type, &self.buf[frame.data]) {
+                let payload = start + payload.start..start + payload.end;
+                if payload.len() >= SEGMENT_HEADER_LEN && self.buf[payload.start] == 1 {
+                    break payload;
+                }
+            }
+        };
+        Segment::parse(&self.buf[range]).map(Some)
+    }
+
+    fn read_frame(&mut self) -> Result<Option<Frame>> {
+        let Capture {
+            ref mut reader,
+            ref mut format,
+            ref mut buf,
+        } = *self;
+        match *format {
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type,
+            } => {
+                let mut header = [0; 16];
+                if !read_or_eof(reader, &mut header)? {
+                    return Ok(None);
+                }
+                let seconds = u64::from(read_u32(&header[0..4], big_endian));
+                let fraction = u64::from(read_u32(&header[4..8], big_endian));
+                let len = read_len(&header[8..12], big_endian)?;
+                buf.resize(len, 0);
+                reader.read_exact(buf)?;
+                Ok(Some(Frame {
+                    timestamp: seconds * 1_000_000_000 + fraction * if nanos { 1 } else { 1000 },
+                    link_type,
+                    data: 0..len,
+                }))
+            }
+            Format::PcapNg {
+                ref mut big_endian,
+                ref mut interfaces,
+            } => loop {
+                let mut header = [0; 4];
+                if !read_or_eof(reader, &mut header)? {
+                    return Ok(None);
+                }
+                if u32::from_le_bytes(header) == PCAPNG_SECTION_HEADER {
+                    *big_endian = read_section_header(reader)?;
+                    interfaces.clear();
+                    continue;
+                }
+                let block_type = read_u32(&header, *big_endian);
+                reader.read_exact(&mut header)?;
+                let len = read_len(&header, *big_endian)?;
+                if len < 12 || len % 4 != 0 {
+                    let reason = format!("pcap-ng block of {} bytes", len);
+                    return Err(DecodeError::new(reason).into());
+                }
+                // The body, then the block length again.
+                buf.resize(len - 8, 0);
+                reader.read_exact(buf)?;
+                let body = &buf[..len - 12];
+                let truncated = || DecodeError::new("pcap-ng block is truncated");
+                match block_type {
+                    PCAPNG_INTERFACE => {
+                        if body.len() < 8 {
+                            return Err(truncated().into());
+                        }
+                        interfaces.push(Interface {
+                            link_type: u32::from(read_u16(&body[0..2], *big_endian)),
+                            resolution: read_resolution(&body[8..], *big_endian),
+                        });
+                    }
+                    PCAPNG_ENHANCED_PACKET => {
+                        if body.len() < 20 {
+                            return Err(truncated().into());
+                        }
+                        let interface = read_u32(&body[0..4], *big_endian) as usize;
+                        let interface = *interfaces.get(interface).ok_or_else(|| {
+                            DecodeError::new(format!("no pcap-ng interface {}", interface))
+                        })?;
+                        let high = u64::from(read_u32(&body[4..8], *big_endian));
+                        let low = u64::from(read_u32(&body[8..12], *big_endian));
+                        let captured = read_u32(&body[12..16], *big_endian) as usize;
+                        // `body.len() >= 20`, and `20 + captured` could
+                        // overflow on 32-bit targets.
+                        if captured > body.len() - 20 {
+                            return Err(truncated().into());
+                        }
+                        return Ok(Some(Frame {
+                            timestamp: interface.nanos(high << 32 | low),
+                            link_type: interface.link_type,
+                            data: 20..20 + captured,
+                        }));
+                    }
+                    PCAPNG_SIMPLE_PACKET => {
+                        let interface = *interfaces
+                            .first()
+                            .ok_or_else(|| DecodeError::new("no pcap-ng interface 0"))?;
+                        if body.len() < 4 {
+                            return Err(truncated().into());
+                        }
+                        let original = read_u32(&body[0..4], *big_endian) as usize;
+                        return Ok(Some(Frame {
+                            timestamp: 0,
+                            link_type: interface.link_type,
+                            data: 4..4 + original.min(body.len() - 4),
+                        }));
+                    }
+                    _ => {}
+                }
+            },
+        }
+    }
+}
+
+/// Reads the rest of a pcap-ng section header block after its type, and
+/// returns whether the section is big-endian.
+fn read_section_header<R: Read>(reader: &mut R) -> Result<bool> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header)?;
+    let big_endian = match header[4..8] {
+        [0x1A, 0x2B, 0x3C, 0x4D] => true,
+        [0x4D, 0x3C, 0x2B, 0x1A] => false,
+        _ => return Err(DecodeError::new("bad pcap-ng byte-order magic").into()),
+    };
+    let len = read_len(&header[0..4], big_endian)?;
+    if len < 12 {
+        let reason = format!("pcap-ng section header of {} bytes", len);
+        return Err(DecodeError::new(reason).into());
+    }
+    io::copy(&mut reader.take(len as u64 - 12), &mut io::sink())?;
+    Ok(big_endian)
+}
+
+/// Returns the `if_tsresol` option of an interface description block,
+/// defaulting to microseconds.
+fn read_resolution(mut options: &[u8], big_endian: bool) -> u8 {
+    while options.len() >= 4 {
+        let code = read_u16(&options[0..2], big_endian);
+        let len = read_u16(&options[2..4], big_endian) as usize;
+        let value = &options[4..];
+        if code == 0 || value.len() < len {
+            break;
+        }
+        if code == 9 && len == 1 {
+            return value[0];
+        }
+        options = &value[((len + 3) & !3).min(value.len())..];
+    }
+    6
+}
+
+/// Fills `buf`, or returns false if the reader is already at its end.
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn read_len(bytes: &[u8], big_endian: bool) -> Result<usize> {
+    let len = read_u32(bytes, big_endian) as usize;
+    if len > MAX_RECORD_LEN {
+        let reason = format!("capture record of {} bytes", len);
+        return Err(DecodeError::new(reason).into());
+    }
+    Ok(len)
+}
+
+/// Returns where the UDP payload is within `frame`, if it is an unfragmented
+/// UDP packet.
+fn udp_payload(link_type: u32, frame: &[u8]) -> Option<Range<usize>> {
+    let be16 = |at: usize| frame.get(at..at + 2).map(|bytes| read_u16(bytes, true));
+    let (ether_type, ip) = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut ether_type = be16(12)?;
+            let mut ip = 14;
+            // VLAN tags.
+            while ether_type == 0x8100 || ether_type == 0x88A8 {
+                ether_type = be16(ip + 2)?;
+                ip += 4;
+            }
+            (ether_type, ip)
+        }
+        LINKTYPE_LINUX_SLL => (be16(14)?, 16),
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => match frame.first()? >> 4 {
+            4 => (0x0800, 0),
+            6 => (0x86DD, 0),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let (protocol, udp) = match ether_type {
+        0x0800 => {
+            let header_len = usize::from(frame.get(ip)? & 0x0f) * 4;
+            // More fragments, or a fragment offset.
+            if be16(ip + 6)? & 0x3fff != 0 {
+                return None;
+            }
+            (*frame.get(ip + 9)?, ip + header_len)
+        }
+        0x86DD => (*frame.get(ip + 6)?, ip + 40),
+        _ => return None,
+    };
+    if protocol != 17 {
+        return None;
+    }
+    let len = usize::from(be16(udp + 4)?);
+    if len < 8 || udp + 8 > frame.len() {
+        return None;
+    }
+    Some(udp + 8..frame.len().min(udp + len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcapng_resolutions() {
+        let interface = |resolution| Interface {
+            link_type: LINKTYPE_ETHERNET,
+            resolution,
+        };
+        assert_eq!(interface(6).nanos(1_500), 1_500_000);
+        assert_eq!(interface(9).nanos(1_500), 1_500);
+        assert_eq!(interface(12).nanos(1_500), 1);
+        assert_eq!(interface(0x80 | 10).nanos(1024), 1_000_000_000);
+    }
+
+    #[test]
+    fn truncated_messages_end_iteration() {
+        let segment = Segment {
+            version: 1,
+            protocol: TOPS_PROTOCOL,
+            channel: 1,
+            session: 1,
+            message_count: 2,
+            stream_offset: 0,
+            first_seq: 1,
+            send_time: 0,
+            payload: &[2, 0, b'S', b'O', 5, 0, b'Q'],
+        };
+        let mut messages = segment.messages();
+        assert_eq!(messages.next().unwrap().unwrap(), b"SO");
+        assert!(messages.next().unwrap().is_err());
+        assert!(messages.next().is_none());
+    }
+}
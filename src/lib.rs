@@ -1,19 +1,80 @@
 #[macro_use]
+extern crate derive_builder;
+#[macro_use]
 extern crate serde_derive;
+extern crate chrono;
 extern crate failure;
 extern crate reqwest;
+#[cfg(feature = "pcap")]
+extern crate flate2;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "decimal")]
+extern crate rust_decimal;
 extern crate serde;
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
 
 use serde_json::Value;
 
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "iex-cloud")]
+mod cloud;
+#[cfg(feature = "iex-cloud")]
+mod data;
+mod de;
+#[cfg(feature = "pcap")]
+mod deep;
 mod endpoints;
+mod hist;
+#[cfg(feature = "pcap")]
+mod iextp;
+mod market;
+mod news;
+#[cfg(feature = "pcap")]
+mod order_book;
+mod replay;
+mod requests;
+mod schema;
+mod stream;
+mod subscription;
+#[cfg(feature = "iex-cloud")]
+mod time_series;
+#[cfg(feature = "pcap")]
+mod tops;
 mod types;
-
+mod watch;
+
+#[cfg(feature = "iex-cloud")]
+pub use self::cloud::*;
+#[cfg(feature = "iex-cloud")]
+pub use self::data::*;
+#[cfg(feature = "pcap")]
+pub use self::deep::*;
 pub use self::endpoints::*;
+pub use self::hist::*;
+#[cfg(feature = "pcap")]
+pub use self::iextp::*;
+pub use self::market::*;
+pub use self::news::*;
+#[cfg(feature = "pcap")]
+pub use self::order_book::*;
+pub use self::replay::*;
+pub use self::requests::*;
+pub use self::schema::*;
+pub use self::stream::*;
+pub use self::subscription::*;
+#[cfg(feature = "iex-cloud")]
+pub use self::time_series::*;
+#[cfg(feature = "pcap")]
+pub use self::tops::*;
 pub use self::types::*;
+pub use self::watch::*;
 
 use failure::Error;
+use std::borrow::Cow;
 use std::result;
 
 pub type Result<T> = result::Result<T, Error>;
@@ -21,31 +82,122 @@ pub type Result<T> = result::Result<T, Error>;
 /// `IEX_URL` is the URL base of IEX API.
 const IEX_URL: &str = "https://api.iextrading.com/1.0";
 
+/// `IEX_CLOUD_URL` is the URL base of the IEX Cloud API.
+#[cfg(feature = "iex-cloud")]
+const IEX_CLOUD_URL: &str = "https://cloud.iexapis.com/stable";
+
+/// `IEX_CLOUD_SSE_URL` is the URL base of IEX Cloud's streaming endpoints.
+#[cfg(feature = "iex-cloud")]
+const IEX_CLOUD_SSE_URL: &str = "https://cloud-sse.iexapis.com/stable";
+
 /// `Client` acts as a Handler for the `Response` enum.
-#[derive(Default)]
-pub struct Client;
+#[derive(Clone)]
+pub struct Client {
+    base_url: Cow<'static, str>,
+    #[cfg(feature = "iex-cloud")]
+    sse_base_url: Cow<'static, str>,
+    token: Option<String>,
+}
+
+/// The default client, as `Client::new()` returns it. `Client` was a unit
+/// struct, and this keeps the `Client` expression working.
+#[allow(non_upper_case_globals)]
+pub const Client: Client = Client {
+    base_url: Cow::Borrowed(IEX_URL),
+    #[cfg(feature = "iex-cloud")]
+    sse_base_url: Cow::Borrowed(IEX_CLOUD_SSE_URL),
+    token: None,
+};
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new()
+    }
+}
 
 impl Client {
     pub fn new() -> Self {
         Client
     }
 
+    /// Creates a client for IEX Cloud, authenticating with `token`.
+    #[cfg(feature = "iex-cloud")]
+    pub fn cloud<S>(token: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Client::new()
+            .with_base_url(IEX_CLOUD_URL)
+            .with_token(token)
+    }
+
+    /// Sends requests to `base_url` instead, e.g. the IEX Cloud sandbox.
+    pub fn with_base_url<S>(mut self, base_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.base_url = Cow::Owned(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Opens streams at `sse_base_url` instead, e.g. the IEX Cloud sandbox.
+    #[cfg(feature = "iex-cloud")]
+    pub fn with_sse_base_url<S>(mut self, sse_base_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.sse_base_url = Cow::Owned(sse_base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Adds `token` to every request, as IEX Cloud requires.
+    pub fn with_token<S>(mut self, token: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Returns the URL for `path`, relative to the base URL, including the
+    /// token if there is one.
+    pub fn url(&self, path: &str) -> String {
+        self.url_from(&self.base_url, path)
+    }
+
+    fn url_from(&self, base_url: &str, path: &str) -> String {
+        let mut url = format!("{}/{}", base_url, path);
+        if let Some(ref token) = self.token {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str("token=");
+            url.push_str(&percent_encode(token));
+        }
+        url
+    }
+
     /// stocks_request is the main entry-point to the IEX Stocks API.
     pub fn stocks_request<S>(&self, symbol: S, req: StocksEndpoint) -> Result<Response>
     where
         S: Into<String>,
     {
-        let url = format!(
-            "{base}/stock/{symbol}/{endpoint}",
-            base = IEX_URL,
+        let url = self.url(&format!(
+            "stock/{symbol}/{endpoint}",
             symbol = symbol.into(),
             endpoint = req.to_endpoint()
-        );
+        ));
 
         Ok(reqwest::get(&url)?.json()?)
     }
+
+    /// Requests an endpoint for the market as a whole, e.g. market-wide news.
+    pub fn market_request(&self, req: StocksEndpoint) -> Result<Response> {
+        self.stocks_request(MARKET, req)
+    }
 }
 
+/// `MARKET` is the symbol IEX uses for market-wide data.
+pub const MARKET: &str = "market";
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Response(pub Value);
 
@@ -57,6 +209,24 @@ impl Response {
     {
         Ok(serde_json::from_value(self.0)?)
     }
+
+    /// Like `try_into`, but fails with `UnknownFields` if the response has
+    /// fields that `T` does not model.
+    pub fn try_into_strict<T>(self) -> Result<T>
+    where
+        T: for<'de> serde::Deserialize<'de> + serde::Serialize,
+    {
+        from_value_strict(self.0)
+    }
+
+    /// Reports fields in this response that `T` does not model, and fields
+    /// modelled by `T` that are missing from the response.
+    pub fn schema_report<T>(&self) -> Result<SchemaReport>
+    where
+        T: for<'de> serde::Deserialize<'de> + serde::Serialize,
+    {
+        schema_report::<T>(&self.0)
+    }
 }
 
 pub trait Endpoint {
@@ -73,6 +243,22 @@ mod tests {
     #[allow(non_upper_case_globals)]
     static duration: Duration = Duration::OneDay;
 
+    #[test]
+    fn client_urls_include_token() {
+        let client = Client::new()
+            .with_base_url("http://localhost:8080/")
+            .with_token("pk_test");
+        assert_eq!(
+            client.url("stock/aapl/quote"),
+            "http://localhost:8080/stock/aapl/quote?token=pk_test"
+        );
+        assert_eq!(
+            client.url("stock/aapl/chart/1d?chartLast=5"),
+            "http://localhost:8080/stock/aapl/chart/1d?chartLast=5&token=pk_test"
+        );
+        assert_eq!(Client::new().url("ref-data/symbols"), format!("{}/ref-data/symbols", IEX_URL));
+    }
+
     #[test]
     fn client_request_book() {
         assert!(CLIENT.stocks_request(symbol, StocksEndpoint::Book).is_ok());
@@ -142,7 +328,13 @@ mod tests {
     fn client_request_financials() {
         assert!(
             CLIENT
-                .stocks_request(symbol, StocksEndpoint::Financials)
+                .stocks_request(
+                    symbol,
+                    StocksEndpoint::Financials {
+                        period: None,
+                        last: None,
+                    }
+                )
                 .is_ok()
         );
     }
@@ -170,7 +362,7 @@ mod tests {
     fn client_request_news() {
         assert!(
             CLIENT
-                .stocks_request(symbol, StocksEndpoint::News { range: None })
+                .stocks_request(symbol, StocksEndpoint::News { last: None })
                 .is_ok()
         );
     }
@@ -0,0 +1,102 @@
+/// Defines an enum for a string field that IEX documents with a fixed set of
+/// values. Anything not listed deserializes to `Unknown`, so new values added
+/// by IEX don't break parsing.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:tt,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not (yet) known to this crate.
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Returns the string IEX uses for this value.
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(ref value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(value: &'a str) -> $name {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value.to_string()),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<$name, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+                Ok($name::from(value.as_str()))
+            }
+        }
+    };
+}
+
+/// Defines an enum for a one-byte field of IEX's binary feeds. Anything not
+/// listed decodes to `Unknown`, as with `string_enum!`.
+#[cfg(feature = "pcap")]
+macro_rules! byte_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:tt,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not (yet) known to this crate.
+            Unknown(u8),
+        }
+
+        impl $name {
+            /// Returns the byte IEX uses for this value.
+            pub fn as_byte(&self) -> u8 {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> $name {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,129 @@
+//! Market-wide responses, keyed by symbol.
+use std::collections::{BTreeMap, HashMap};
+
+use endpoints::{percent_encode, StocksEndpoint};
+use types::{CryptoQuote, Previous, Quote, OHLC};
+use {Client, Result, MARKET};
+
+/// The most symbols IEX accepts in one batch request.
+const BATCH_SIZE: usize = 100;
+
+/// Response of `ohlc` for the `market` symbol.
+pub type MarketOhlc = HashMap<String, OHLC>;
+
+/// Response of `previous` for the `market` symbol.
+pub type MarketPrevious = HashMap<String, Previous>;
+
+/// `DailySnapshot` is one symbol's row in a daily snapshot table: today's
+/// official open and close alongside the previous trading day. Either half
+/// may be missing, e.g. for a symbol that listed today.
+#[derive(Debug)]
+pub struct DailySnapshot {
+    pub symbol: String,
+    pub ohlc: Option<OHLC>,
+    pub previous: Option<Previous>,
+}
+
+impl DailySnapshot {
+    fn new(symbol: String) -> DailySnapshot {
+        DailySnapshot {
+            symbol,
+            ohlc: None,
+            previous: None,
+        }
+    }
+}
+
+/// Merges market-wide OHLC and previous day data into a table sorted by
+/// symbol, with a row for every symbol in either map.
+pub fn daily_snapshots(
+    ohlc: MarketOhlc,
+    previous: MarketPrevious,
+) -> BTreeMap<String, DailySnapshot> {
+    let mut table = BTreeMap::new();
+    for (symbol, ohlc) in ohlc {
+        table
+            .entry(symbol.clone())
+            .or_insert_with(|| DailySnapshot::new(symbol))
+            .ohlc = Some(ohlc);
+    }
+    for (symbol, previous) in previous {
+        table
+            .entry(symbol.clone())
+            .or_insert_with(|| DailySnapshot::new(symbol))
+            .previous = Some(previous);
+    }
+    table
+}
+
+/// One symbol's entry in a batch response.
+#[derive(Deserialize)]
+struct BatchQuote {
+    quote: Quote,
+}
+
+impl Client {
+    pub fn market_ohlc(&self) -> Result<MarketOhlc> {
+        self.market_request(StocksEndpoint::Ohlc)?.try_into()
+    }
+
+    pub fn market_previous(&self) -> Result<MarketPrevious> {
+        self.market_request(StocksEndpoint::Previous)?.try_into()
+    }
+
+    pub fn crypto_quotes(&self) -> Result<Vec<CryptoQuote>> {
+        self.market_request(StocksEndpoint::Crypto)?.try_into()
+    }
+
+    /// Fetches quotes for `symbols` with batch requests, keyed by symbol as
+    /// IEX returns it. Unknown symbols are left out.
+    pub fn quotes<S>(&self, symbols: &[S]) -> Result<HashMap<String, Quote>>
+    where
+        S: AsRef<str>,
+    {
+        let mut quotes = HashMap::new();
+        for batch in symbols.chunks(BATCH_SIZE) {
+            let batch: Vec<String> = batch.iter().map(|s| percent_encode(s.as_ref())).collect();
+            let url = self.url(&format!(
+                "stock/{}/batch?symbols={}&types=quote",
+                MARKET,
+                batch.join(",")
+            ));
+            let batch: HashMap<String, BatchQuote> = ::reqwest::get(&url)?.json()?;
+            quotes.extend(batch.into_iter().map(|(symbol, batch)| (symbol, batch.quote)));
+        }
+        Ok(quotes)
+    }
+
+    /// Fetches market-wide OHLC and previous day data and merges them with
+    /// `daily_snapshots`.
+    pub fn daily_snapshots(&self) -> Result<BTreeMap<String, DailySnapshot>> {
+        Ok(daily_snapshots(self.market_ohlc()?, self.market_previous()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn snapshots_include_symbols_from_either_map() {
+        let ohlc: MarketOhlc =
+            serde_json::from_str(include_str!("../tests/fixtures/market_ohlc.json")).unwrap();
+        let previous: MarketPrevious =
+            serde_json::from_str(include_str!("../tests/fixtures/market_previous.json")).unwrap();
+
+        let table = daily_snapshots(ohlc, previous);
+        let symbols: Vec<&str> = table.keys().map(String::as_str).collect();
+        assert_eq!(symbols, vec!["AAPL", "DBX", "SPY", "ZXIET"]);
+
+        let aapl = &table["AAPL"];
+        assert_eq!(aapl.symbol, "AAPL");
+        assert!(aapl.ohlc.is_some());
+        assert_eq!(aapl.previous.as_ref().unwrap().date, "2018-03-22");
+
+        assert!(table["DBX"].ohlc.is_none());
+        assert!(table["ZXIET"].previous.is_none());
+    }
+}
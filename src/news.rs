@@ -0,0 +1,134 @@
+//! Polling for news without repeating articles.
+use std::collections::{HashSet, VecDeque};
+use std::thread;
+use std::time;
+
+use endpoints::{NewsCount, StocksEndpoint};
+use types::News;
+use {Client, Result};
+
+/// `NewsFeed` is an endless iterator over news articles, fetched by polling
+/// every `interval`. Articles already returned by an earlier poll are
+/// skipped, and each poll's new articles are returned oldest first.
+pub struct NewsFeed<'a> {
+    poll: Box<dyn FnMut() -> Result<Vec<News>> + 'a>,
+    interval: time::Duration,
+    seen: HashSet<String>,
+    pending: VecDeque<News>,
+    polled: bool,
+}
+
+impl<'a> NewsFeed<'a> {
+    /// Creates a feed from any function returning the latest articles,
+    /// newest first, as IEX does.
+    pub fn new<F>(interval: time::Duration, poll: F) -> NewsFeed<'a>
+    where
+        F: FnMut() -> Result<Vec<News>> + 'a,
+    {
+        NewsFeed {
+            poll: Box::new(poll),
+            interval,
+            seen: HashSet::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        }
+    }
+}
+
+impl<'a> Iterator for NewsFeed<'a> {
+    type Item = Result<News>;
+
+    fn next(&mut self) -> Option<Result<News>> {
+        loop {
+            if let Some(news) = self.pending.pop_front() {
+                return Some(Ok(news));
+            }
+            if self.polled {
+                thread::sleep(self.interval);
+            }
+            self.polled = true;
+
+            let articles = match (self.poll)() {
+                Ok(articles) => articles,
+                Err(e) => return Some(Err(e)),
+            };
+            if articles.is_empty() {
+                continue;
+            }
+            // Only the latest poll needs remembering: an article that has
+            // dropped out of the last N won't come back.
+            let seen: HashSet<String> = articles.iter().map(|news| news.url.clone()).collect();
+            for news in articles.into_iter().rev() {
+                if !self.seen.contains(&news.url) {
+                    self.pending.push_back(news);
+                }
+            }
+            self.seen = seen;
+        }
+    }
+}
+
+impl Client {
+    /// Polls news for `symbol` (or `MARKET`) every `interval`, returning
+    /// each article once.
+    pub fn news_feed<'a, S>(
+        &'a self,
+        symbol: S,
+        last: Option<NewsCount>,
+        interval: time::Duration,
+    ) -> NewsFeed<'a>
+    where
+        S: Into<String>,
+    {
+        let symbol = symbol.into();
+        NewsFeed::new(interval, move || {
+            self.stocks_request(symbol.as_str(), StocksEndpoint::News { last })?
+                .try_into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn news(urls: &[&str]) -> Vec<News> {
+        urls.iter()
+            .map(|url| {
+                serde_json::from_value(json!({
+                    "datetime": "2017-06-29T13:14:22-04:00",
+                    "headline": "Headline",
+                    "source": "Source",
+                    "url": url,
+                    "summary": "Summary",
+                    "related": "AAPL",
+                })).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn feed_skips_articles_seen_in_earlier_polls() {
+        let mut polls = vec![
+            news(&["c", "b", "a"]),
+            news(&[]),
+            news(&["d", "c", "b"]),
+            news(&["e", "d", "c"]),
+        ].into_iter();
+        let feed = NewsFeed::new(time::Duration::from_millis(0), move || {
+            Ok(polls.next().unwrap_or_default())
+        });
+
+        let urls: Vec<String> = feed.take(5).map(|news| news.unwrap().url).collect();
+        assert_eq!(urls, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn feed_returns_poll_errors() {
+        let mut feed = NewsFeed::new(time::Duration::from_millis(0), || {
+            Err(::failure::err_msg("unavailable"))
+        });
+        assert!(feed.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Order books rebuilt locally from DEEP updates.
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::Map;
+
+use deep::{DeepFeedMessage, PriceLevelUpdate, Side};
+use stream::{DeepData, Event};
+use tops::{millis, TradingStatus};
+use types::{Ask, Bid, Book, Money, Quote, SystemEvent, Trade};
+
+/// How many of the latest trades an `OrderBook` keeps.
+const RECENT_TRADES: usize = 20;
+
+/// One price level of a book. Timestamps are in milliseconds, as in `Bid`
+/// and `Ask`.
+#[derive(Clone, Copy, Debug)]
+struct Level {
+    price: Money,
+    size: u64,
+    timestamp: u64,
+}
+
+impl Level {
+    fn bid(&self) -> Bid {
+        Bid {
+            price: self.price,
+            size: self.size,
+            timestamp: self.timestamp,
+            extra: Map::new(),
+        }
+    }
+
+    fn ask(&self) -> Ask {
+        Ask {
+            price: self.price,
+            size: self.size,
+            timestamp: self.timestamp,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// `OrderBook` is the book of one symbol, kept up to date from DEEP
+/// messages: price levels, trading status and the latest trades.
+///
+/// A DEEP order book event can update several price levels, and the book is
+/// only consistent once the whole event is in. Updates are held back until
+/// the one completing their event arrives and then applied together, so the
+/// book never shows an event half done.
+#[derive(Clone, Debug)]
+pub struct OrderBook {
+    symbol: String,
+    /// Best (highest) first.
+    bids: Vec<Level>,
+    /// Best (lowest) first.
+    asks: Vec<Level>,
+    pending: Vec<(Side, Level)>,
+    trading_status: Option<TradingStatus>,
+    trades: VecDeque<Trade>,
+    timestamp: u64,
+}
+
+impl OrderBook {
+    pub fn new<S: Into<String>>(symbol: S) -> OrderBook {
+        OrderBook {
+            symbol: symbol.into(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            pending: Vec::new(),
+            trading_status: None,
+            trades: VecDeque::new(),
+            timestamp: 0,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns when the book last changed, in milliseconds since the epoch.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns the latest trading status, if one has been seen. Levels are
+    /// kept through halts and pauses, but aren't tradable then.
+    pub fn trading_status(&self) -> Option<TradingStatus> {
+        self.trading_status
+    }
+
+    /// Whether updates of an unfinished order book event are held back.
+    pub fn in_transition(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn best_bid(&self) -> Option<Bid> {
+        self.bids.first().map(Level::bid)
+    }
+
+    pub fn best_ask(&self) -> Option<Ask> {
+        self.asks.first().map(Level::ask)
+    }
+
+    /// Returns up to `depth` bids, best first.
+    pub fn bids(&self, depth: usize) -> Vec<Bid> {
+        self.bids.iter().take(depth).map(Level::bid).collect()
+    }
+
+    /// Returns up to `depth` asks, best first.
+    pub fn asks(&self, depth: usize) -> Vec<Ask> {
+        self.asks.iter().take(depth).map(Level::ask).collect()
+    }
+
+    /// Returns the latest trades, oldest first, without broken trades.
+    pub fn trades(&self) -> impl Iterator<Item = &Trade> {
+        self.trades.iter()
+    }
+
+    /// Returns the book to `depth` levels a side.
+    pub fn snapshot(&self, depth: usize) -> BookSnapshot {
+        BookSnapshot {
+            symbol: self.symbol.clone(),
+            timestamp: self.timestamp,
+            trading_status: self.trading_status,
+            bids: self.bids(depth),
+            asks: self.asks(depth),
+            trades: self.trades.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Applies a message of the DEEP binary feed. Messages about other
+    /// symbols are ignored.
+    pub fn apply(&mut self, message: &DeepFeedMessage) {
+        if message.symbol() != Some(self.symbol.as_str()) {
+            return;
+        }
+        match *message {
+            DeepFeedMessage::PriceLevelUpdate(ref update) => self.update(update),
+            DeepFeedMessage::TradingStatus(ref status) => {
+                self.trading_status = Some(status.status);
+                self.timestamp = self.timestamp.max(millis(status.timestamp));
+            }
+            DeepFeedMessage::TradeReport(ref report) => self.trade(Trade::from(report.clone())),
+            DeepFeedMessage::TradeBreak(ref report) => {
+                self.break_trade(report.trade_id, millis(report.timestamp))
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies an event of the DEEP stream: `book` snapshots, trades,
+    /// `tradingStatus` and `tradeBreak`. Events about other symbols are
+    /// ignored.
+    pub fn apply_event(&mut self, event: &Event) {
+        match *event {
+            Event::Trade {
+                ref symbol,
+                ref trade,
+                ..
+            } if symbol.eq_ignore_ascii_case(&self.symbol) => self.trade(trade.clone()),
+            Event::Deep(ref message) if message.symbol.eq_ignore_ascii_case(&self.symbol) => {
+                match message.data {
+                    DeepData::Book(ref book) => {
+                        let level = |price, size, timestamp| Level {
+                            price,
+                            size,
+                            timestamp,
+                        };
+                        self.bids = book
+                            .bids
+                            .iter()
+                            .map(|bid| level(bid.price, bid.size, bid.timestamp))
+                            .collect();
+                        self.asks = book
+                            .asks
+                            .iter()
+                            .map(|ask| level(ask.price, ask.size, ask.timestamp))
+                            .collect();
+                        self.pending.clear();
+                        let latest = self.bids.iter().chain(&self.asks).map(|l| l.timestamp).max();
+                        self.timestamp = self.timestamp.max(latest.unwrap_or(0));
+                    }
+                    DeepData::TradingStatus(ref status) => {
+                        let status = status.status.bytes().next().unwrap_or_default();
+                        self.trading_status = Some(TradingStatus::from(status));
+                    }
+                    DeepData::TradeBreak(ref broken) => {
+                        self.break_trade(broken.trade_id, broken.timestamp);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, update: &PriceLevelUpdate) {
+        let level = Level {
+            price: update.price,
+            size: u64::from(update.size),
+            timestamp: millis(update.timestamp),
+        };
+        self.pending.push((update.side, level));
+        if !update.is_event_complete() {
+            return;
+        }
+        for (side, level) in self.pending.drain(..) {
+            match side {
+                Side::Buy => set_level(&mut self.bids, level, Ordering::Greater),
+                Side::Sell => set_level(&mut self.asks, level, Ordering::Less),
+            }
+        }
+        self.timestamp = self.timestamp.max(level.timestamp);
+    }
+
+    fn trade(&mut self, trade: Trade) {
+        self.timestamp = self.timestamp.max(trade.timestamp);
+        if self.trades.len() == RECENT_TRADES {
+            self.trades.pop_front();
+        }
+        self.trades.push_back(trade);
+    }
+
+    fn break_trade(&mut self, trade_id: u64, timestamp: u64) {
+        self.trades.retain(|trade| trade.trade_id != trade_id);
+        self.timestamp = self.timestamp.max(timestamp);
+    }
+}
+
+/// Sets the size at a level of one side, removing it at size zero. `best`
+/// is how a better price compares to a worse one.
+fn set_level(levels: &mut Vec<Level>, level: Level, best: Ordering) {
+    let position = levels.binary_search_by(|probe| {
+        match probe.price.partial_cmp(&level.price) {
+            Some(Ordering::Equal) | None => Ordering::Equal,
+            Some(ordering) if ordering == best => Ordering::Less,
+            Some(_) => Ordering::Greater,
+        }
+    });
+    match position {
+        Ok(i) if level.size == 0 => {
+            levels.remove(i);
+        }
+        Ok(i) => levels[i] = level,
+        Err(_) if level.size == 0 => {}
+        Err(i) => levels.insert(i, level),
+    }
+}
+
+/// `BookSnapshot` is an `OrderBook` at one moment, to some depth.
+#[derive(Clone, Debug)]
+pub struct BookSnapshot {
+    pub symbol: String,
+    /// In milliseconds since the epoch.
+    pub timestamp: u64,
+    pub trading_status: Option<TradingStatus>,
+    /// Best first.
+    pub bids: Vec<Bid>,
+    /// Best first.
+    pub asks: Vec<Ask>,
+    /// Newest first, as in `Book`.
+    pub trades: Vec<Trade>,
+}
+
+impl BookSnapshot {
+    /// Makes the snapshot into a `Book`, which also holds a quote and the
+    /// latest system event.
+    pub fn into_book(self, quote: Quote, system_event: SystemEvent) -> Book {
+        Book {
+            quote,
+            bids: self.bids,
+            asks: self.asks,
+            trades: self.trades,
+            system_event,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// `OrderBooks` keeps an `OrderBook` for every symbol it sees updates for.
+#[derive(Clone, Debug, Default)]
+pub struct OrderBooks {
+    books: HashMap<String, OrderBook>,
+}
+
+impl OrderBooks {
+    pub fn new() -> OrderBooks {
+        OrderBooks::default()
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&OrderBook> {
+        self.books.get(symbol)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &OrderBook> {
+        self.books.values()
+    }
+
+    /// Applies a message of the DEEP binary feed to its symbol's book.
+    pub fn apply(&mut self, message: &DeepFeedMessage) {
+        if let Some(symbol) = message.symbol() {
+            self.books
+                .entry(symbol.to_string())
+                .or_insert_with(|| OrderBook::new(symbol))
+                .apply(message);
+        }
+    }
+
+    /// Applies an event of the DEEP stream to its symbol's book.
+    pub fn apply_event(&mut self, event: &Event) {
+        if let Some(symbol) = event.symbol() {
+            self.books
+                .entry(symbol.to_uppercase())
+                .or_insert_with(|| OrderBook::new(symbol.to_uppercase()))
+                .apply_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(s: &str) -> Money {
+        s.parse().unwrap()
+    }
+
+    fn update(side: Side, flags: u8, size: u32, price: &str) -> DeepFeedMessage<'static> {
+        DeepFeedMessage::PriceLevelUpdate(PriceLevelUpdate {
+            side,
+            flags,
+            timestamp: 1_000_000,
+            symbol: "SPY",
+            size,
+            price: money(price),
+        })
+    }
+
+    fn prices(bids: &[Bid]) -> Vec<(String, u64)> {
+        bids.iter()
+            .map(|bid| (bid.price.to_string(), bid.size))
+            .collect()
+    }
+
+    #[test]
+    fn levels_stay_sorted_and_empty_levels_go() {
+        let mut book = OrderBook::new("SPY");
+        for message in &[
+            update(Side::Buy, 1, 100, "10.01"),
+            update(Side::Buy, 1, 200, "10.03"),
+            update(Side::Buy, 1, 300, "10.02"),
+            update(Side::Buy, 1, 0, "10.03"),
+            update(Side::Buy, 1, 400, "10.01"),
+            update(Side::Buy, 1, 0, "9.99"),
+        ] {
+            book.apply(message);
+        }
+        assert_eq!(
+            prices(&book.bids(10)),
+            vec![("10.02".to_string(), 300), ("10.01".to_string(), 400)]
+        );
+        assert_eq!(prices(&book.bids(1)), vec![("10.02".to_string(), 300)]);
+    }
+
+    #[test]
+    fn events_apply_atomically() {
+        let mut book = OrderBook::new("SPY");
+        book.apply(&update(Side::Sell, 1, 100, "10.05"));
+        book.apply(&update(Side::Sell, 0, 0, "10.05"));
+        book.apply(&update(Side::Sell, 0, 100, "10.06"));
+        assert!(book.in_transition());
+        assert_eq!(book.best_ask().unwrap().price, money("10.05"));
+
+        book.apply(&update(Side::Buy, 1, 100, "10.04"));
+        assert!(!book.in_transition());
+        assert_eq!(book.best_ask().unwrap().price, money("10.06"));
+        assert_eq!(book.best_bid().unwrap().price, money("10.04"));
+    }
+
+    #[test]
+    fn late_updates_keep_the_latest_timestamp() {
+        let at = |timestamp, price| match update(Side::Buy, 1, 100, price) {
+            DeepFeedMessage::PriceLevelUpdate(update) => {
+                DeepFeedMessage::PriceLevelUpdate(PriceLevelUpdate { timestamp, ..update })
+            }
+            _ => unreachable!(),
+        };
+        let mut book = OrderBook::new("SPY");
+        book.apply(&at(2_000_000_000, "10.01"));
+        book.apply(&at(1_000_000_000, "10.02"));
+        assert_eq!(book.timestamp(), 2000);
+        assert_eq!(book.best_bid().unwrap().timestamp, 1000);
+    }
+}
@@ -0,0 +1,696 @@
+//! Replays of recorded streams and HIST files as streaming events.
+#[cfg(feature = "pcap")]
+use std::collections::{HashMap, VecDeque};
+use std::collections::HashSet;
+#[cfg(feature = "pcap")]
+use std::error;
+#[cfg(feature = "pcap")]
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+#[cfg(feature = "pcap")]
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time;
+
+#[cfg(feature = "pcap")]
+use chrono::{self, Datelike, NaiveDate, TimeZone, Utc};
+#[cfg(feature = "pcap")]
+use serde_json::Map;
+
+#[cfg(feature = "pcap")]
+use deep::{DeepFeedMessage, SecurityEventType};
+#[cfg(feature = "pcap")]
+use iextp::{Capture, DEEP_PROTOCOL, TOPS_PROTOCOL};
+#[cfg(feature = "pcap")]
+use order_book::OrderBooks;
+#[cfg(feature = "pcap")]
+use stream::{DeepData, DeepMessage};
+use stream::{Channel, Event, EventStream};
+#[cfg(feature = "pcap")]
+use tops::{
+    millis, AuctionType, ImbalanceSide, OfficialPriceType, OperationalHaltStatus, QuoteUpdate,
+    TopsMessage,
+};
+#[cfg(feature = "pcap")]
+use types::{
+    DeepAuction, DeepBook, DeepOfficialPrice, DeepOpHaltStatus, DeepSecurityEvent, DeepSsr,
+    DeepTradingStatus, LastSale, Money, SecurityEventKind, SystemEvent, Tops, Trade,
+};
+use Result;
+
+/// How fast a `Replay` plays events back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    /// Waits out the recorded time between events, divided by the factor:
+    /// `Multiplier(1.0)` replays in real time, `Multiplier(10.0)` ten times
+    /// as fast. A factor that isn't positive doesn't wait at all.
+    Multiplier(f64),
+    /// Doesn't wait between events.
+    AsFastAsPossible,
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Speed::Multiplier(1.0)
+    }
+}
+
+/// `Clock` is the simulated time of a `Replay`: the time of the latest event
+/// it returned, in milliseconds since the epoch. Clones share the time, so
+/// code under test can read it while the replay runs.
+#[derive(Clone, Debug, Default)]
+pub struct Clock(Arc<AtomicU64>);
+
+impl Clock {
+    /// Returns the simulated time, or `None` before the first event.
+    pub fn now(&self) -> Option<u64> {
+        match self.0.load(Ordering::SeqCst) {
+            0 => None,
+            now => Some(now),
+        }
+    }
+
+    fn set(&self, now: u64) {
+        self.0.store(now, Ordering::SeqCst);
+    }
+}
+
+/// `Replay` plays recorded events back through the interface of a live
+/// `EventStream`, paced by their timestamps, so code consuming live events
+/// can be run deterministically against a past day.
+///
+/// Events are expected in time order: the replay ends at the first event
+/// after `ending_at`. Events without a timestamp aren't paced or filtered by
+/// time, and events without a symbol, such as system events, pass any symbol
+/// filter.
+pub struct Replay<'a> {
+    events: Box<dyn Iterator<Item = Result<Event>> + 'a>,
+    speed: Speed,
+    start: Option<u64>,
+    end: Option<u64>,
+    symbols: Option<HashSet<String>>,
+    clock: Clock,
+    /// The first paced event's time, and when it was returned.
+    anchor: Option<(u64, time::Instant)>,
+    done: bool,
+}
+
+impl<'a> Replay<'a> {
+    /// Creates a replay of any events, such as those of an `EventStream`.
+    pub fn new<I>(events: I) -> Replay<'a>
+    where
+        I: Iterator<Item = Result<Event>> + 'a,
+    {
+        Replay {
+            events: Box::new(events),
+            speed: Speed::default(),
+            start: None,
+            end: None,
+            symbols: None,
+            clock: Clock::default(),
+            anchor: None,
+            done: false,
+        }
+    }
+
+    /// Replays a saved body of a `channel` stream.
+    pub fn recorded<P: AsRef<Path>>(path: P, channel: Channel) -> Result<Replay<'static>> {
+        let file = BufReader::new(File::open(path)?);
+        Ok(Replay::new(EventStream::new(channel, file)))
+    }
+
+    /// Replays a HIST file, TOPS or DEEP, as the events of a `channel`
+    /// stream: `Tops` and `Last` from either, or `Deep`. DEEP order book
+    /// events are replayed as `book` messages once complete.
+    #[cfg(feature = "pcap")]
+    pub fn hist<P: AsRef<Path>>(path: P, channel: Channel) -> Result<Replay<'static>> {
+        match channel {
+            Channel::Tops | Channel::Last | Channel::Deep => {}
+            channel => return Err(UnsupportedChannel { channel }.into()),
+        }
+        Ok(Replay::new(HistEvents {
+            capture: Capture::open(path)?,
+            converter: Converter {
+                channel,
+                tops: HashMap::new(),
+                books: OrderBooks::new(),
+            },
+            pending: VecDeque::new(),
+            done: false,
+        }))
+    }
+
+    pub fn with_speed(mut self, speed: Speed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Skips events before `start`, in milliseconds since the epoch.
+    pub fn starting_at(mut self, start: u64) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Ends the replay at the first event after `end`, in milliseconds since
+    /// the epoch.
+    pub fn ending_at(mut self, end: u64) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Returns only events about `symbols`, and those about no symbol.
+    pub fn with_symbols<S: AsRef<str>>(mut self, symbols: &[S]) -> Self {
+        self.symbols = Some(symbols.iter().map(|s| s.as_ref().to_uppercase()).collect());
+        self
+    }
+
+    /// Returns the replay's simulated clock.
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    fn wanted(&self, event: &Event) -> bool {
+        match (&self.symbols, event.symbol()) {
+            (Some(symbols), Some(symbol)) if !symbol.is_empty() => {
+                symbols.contains(&symbol.to_uppercase())
+            }
+            _ => true,
+        }
+    }
+
+    /// Waits until `timestamp` is due at the replay's speed.
+    fn pace(&mut self, timestamp: u64) {
+        let factor = match self.speed {
+            Speed::Multiplier(factor) if factor > 0.0 => factor,
+            _ => return,
+        };
+        let (first, began) = *self
+            .anchor
+            .get_or_insert_with(|| (timestamp, time::Instant::now()));
+        let recorded = time::Duration::from_millis(timestamp.saturating_sub(first));
+        let elapsed = began.elapsed();
+        let wait = scaled(recorded, factor);
+        if wait > elapsed {
+            thread::sleep(wait - elapsed);
+        }
+    }
+}
+
+/// Returns `recorded` divided by `factor`, saturating at the longest
+/// `Duration` for factors so small the division overflows.
+fn scaled(recorded: time::Duration, factor: f64) -> time::Duration {
+    time::Duration::try_from_secs_f64(recorded.as_secs_f64() / factor)
+        .unwrap_or(time::Duration::MAX)
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            let event = match self.events.next() {
+                Some(Ok(event)) => event,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            if !self.wanted(&event) {
+                continue;
+            }
+            if let Some(timestamp) = event.timestamp() {
+                if self.start.is_some_and(|start| timestamp < start) {
+                    continue;
+                }
+                if self.end.is_some_and(|end| timestamp > end) {
+                    self.done = true;
+                    return None;
+                }
+                self.pace(timestamp);
+                self.clock.set(timestamp);
+            }
+            return Some(Ok(event));
+        }
+    }
+}
+
+/// `UnsupportedChannel` is returned for a HIST replay of a channel HIST files
+/// have no data for.
+#[cfg(feature = "pcap")]
+#[derive(Debug)]
+pub struct UnsupportedChannel {
+    pub channel: Channel,
+}
+
+#[cfg(feature = "pcap")]
+impl fmt::Display for UnsupportedChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} can't be replayed from HIST files", self.channel)
+    }
+}
+
+#[cfg(feature = "pcap")]
+impl error::Error for UnsupportedChannel {}
+
+/// The events of a HIST file, as a `channel` stream would send them.
+#[cfg(feature = "pcap")]
+struct HistEvents {
+    capture: Capture<Box<dyn Read>>,
+    converter: Converter,
+    pending: VecDeque<Event>,
+    done: bool,
+}
+
+#[cfg(feature = "pcap")]
+impl HistEvents {
+    fn read_segment(&mut self) -> Result<()> {
+        let segment = match self.capture.next_segment()? {
+            Some(segment) => segment,
+            None => {
+                self.done = true;
+                return Ok(());
+            }
+        };
+        for (n, message) in segment.messages().enumerate() {
+            let message = match segment.protocol {
+                TOPS_PROTOCOL => Message::from(TopsMessage::parse(message?)?),
+                DEEP_PROTOCOL => Message::Deep(DeepFeedMessage::parse(message?)?),
+                _ => continue,
+            };
+            let seq = segment.first_seq + n as u64;
+            self.converter.convert(seq, message, &mut self.pending);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "pcap")]
+impl Iterator for HistEvents {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.read_segment() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// A TOPS or DEEP message. Only quote updates are TOPS-only.
+#[cfg(feature = "pcap")]
+enum Message<'a> {
+    Quote(QuoteUpdate<'a>),
+    Deep(DeepFeedMessage<'a>),
+}
+
+#[cfg(feature = "pcap")]
+impl<'a> From<TopsMessage<'a>> for Message<'a> {
+    fn from(message: TopsMessage<'a>) -> Message<'a> {
+        Message::Deep(match message {
+            TopsMessage::QuoteUpdate(m) => return Message::Quote(m),
+            TopsMessage::SystemEvent(m) => DeepFeedMessage::SystemEvent(m),
+            TopsMessage::SecurityDirectory(m) => DeepFeedMessage::SecurityDirectory(m),
+            TopsMessage::TradingStatus(m) => DeepFeedMessage::TradingStatus(m),
+            TopsMessage::OperationalHalt(m) => DeepFeedMessage::OperationalHalt(m),
+            TopsMessage::ShortSalePriceTest(m) => DeepFeedMessage::ShortSalePriceTest(m),
+            TopsMessage::TradeReport(m) => DeepFeedMessage::TradeReport(m),
+            TopsMessage::OfficialPrice(m) => DeepFeedMessage::OfficialPrice(m),
+            TopsMessage::TradeBreak(m) => DeepFeedMessage::TradeBreak(m),
+            TopsMessage::AuctionInformation(m) => DeepFeedMessage::AuctionInformation(m),
+            TopsMessage::Unknown { message_type, data } => {
+                DeepFeedMessage::Unknown { message_type, data }
+            }
+        })
+    }
+}
+
+/// Turns binary messages into the events of a stream.
+#[cfg(feature = "pcap")]
+struct Converter {
+    channel: Channel,
+    /// The latest TOPS of each symbol, for `Tops`.
+    tops: HashMap<String, Tops>,
+    books: OrderBooks,
+}
+
+#[cfg(feature = "pcap")]
+impl Converter {
+    fn convert(&mut self, seq: u64, message: Message, events: &mut VecDeque<Event>) {
+        match self.channel {
+            Channel::Tops => self.tops(seq, message, events),
+            Channel::Last => {
+                if let Message::Deep(DeepFeedMessage::TradeReport(ref report)) = message {
+                    events.push_back(Event::Last(LastSale {
+                        symbol: report.symbol.to_string(),
+                        price: report.price,
+                        size: u64::from(report.size),
+                        time: millis(report.timestamp),
+                        seq: Some(seq),
+                        extra: Map::new(),
+                    }));
+                }
+            }
+            _ => {
+                if let Message::Deep(message) = message {
+                    events.extend(self.deep(seq, message));
+                }
+            }
+        }
+    }
+
+    fn tops(&mut self, seq: u64, message: Message, events: &mut VecDeque<Event>) {
+        let tops = match message {
+            Message::Quote(ref quote) => {
+                let tops = latest_tops(&mut self.tops, quote.symbol);
+                tops.bid_price = quote.bid_price;
+                tops.bid_size = u64::from(quote.bid_size);
+                tops.ask_price = quote.ask_price;
+                tops.ask_size = u64::from(quote.ask_size);
+                tops.last_updated = millis(quote.timestamp);
+                tops
+            }
+            // DEEP has no quotes; its book's best levels are the quote.
+            Message::Deep(DeepFeedMessage::PriceLevelUpdate(ref update)) => {
+                self.books
+                    .apply(&DeepFeedMessage::PriceLevelUpdate(update.clone()));
+                if !update.is_event_complete() {
+                    return;
+                }
+                let book = self.books.get(update.symbol);
+                let bid = book.and_then(|book| book.best_bid());
+                let ask = book.and_then(|book| book.best_ask());
+                let tops = latest_tops(&mut self.tops, update.symbol);
+                tops.bid_price = bid.as_ref().map(|bid| bid.price).unwrap_or_default();
+                tops.bid_size = bid.map(|bid| bid.size).unwrap_or_default();
+                tops.ask_price = ask.as_ref().map(|ask| ask.price).unwrap_or_default();
+                tops.ask_size = ask.map(|ask| ask.size).unwrap_or_default();
+                tops.last_updated = millis(update.timestamp);
+                tops
+            }
+            Message::Deep(DeepFeedMessage::TradeReport(ref report)) => {
+                let tops = latest_tops(&mut self.tops, report.symbol);
+                tops.last_sale_price = report.price;
+                tops.last_sale_size = u64::from(report.size);
+                tops.last_sale_time = millis(report.timestamp);
+                tops.last_updated = millis(report.timestamp);
+                tops.volume += u64::from(report.size);
+                tops
+            }
+            Message::Deep(_) => return,
+        };
+        tops.seq = Some(seq);
+        events.push_back(Event::Tops(tops.clone()));
+    }
+
+    fn deep(&mut self, seq: u64, message: DeepFeedMessage) -> Option<Event> {
+        let symbol = message.symbol().unwrap_or_default().to_string();
+        let (message_type, data) = match message {
+            DeepFeedMessage::SystemEvent(event) => {
+                return Some(Event::SystemEvent {
+                    symbol: String::new(),
+                    event: SystemEvent::from(event),
+                    seq: Some(seq),
+                })
+            }
+            DeepFeedMessage::PriceLevelUpdate(ref update) => {
+                self.books.apply(&message);
+                let book = match self.books.get(update.symbol) {
+                    Some(book) if update.is_event_complete() => book,
+                    _ => return None,
+                };
+                (
+                    "book",
+                    DeepData::Book(DeepBook {
+                        bids: book.bids(usize::MAX),
+                        asks: book.asks(usize::MAX),
+                        timestamp: Some(book.timestamp()),
+                        extra: Map::new(),
+                    }),
+                )
+            }
+            DeepFeedMessage::TradeReport(report) => {
+                return Some(Event::Trade {
+                    symbol,
+                    trade: Trade::from(report),
+                    seq: Some(seq),
+                })
+            }
+            DeepFeedMessage::TradeBreak(report) => {
+                ("tradeBreak", DeepData::TradeBreak(Trade::from(report)))
+            }
+            DeepFeedMessage::TradingStatus(ref status) => (
+                "tradingStatus",
+                DeepData::TradingStatus(DeepTradingStatus {
+                    status: char::from(status.status.as_byte()).to_string(),
+                    reason: status.reason.to_string(),
+                    timestamp: millis(status.timestamp),
+                    extra: Map::new(),
+                }),
+            ),
+            DeepFeedMessage::OperationalHalt(ref halt) => (
+                "opHaltStatus",
+                DeepData::OpHaltStatus(DeepOpHaltStatus {
+                    is_halted: halt.status == OperationalHaltStatus::Halted,
+                    timestamp: millis(halt.timestamp),
+                    extra: Map::new(),
+                }),
+            ),
+            DeepFeedMessage::ShortSalePriceTest(ref test) => (
+                "ssr",
+                DeepData::Ssr(DeepSsr {
+                    is_ssr: test.in_effect,
+                    detail: char::from(test.detail.as_byte()).to_string(),
+                    timestamp: millis(test.timestamp),
+                    extra: Map::new(),
+                }),
+            ),
+            DeepFeedMessage::SecurityEvent(ref event) => {
+                let security_event = match event.event {
+                    SecurityEventType::OpeningProcessComplete => SecurityEventKind::MarketOpen,
+                    SecurityEventType::ClosingProcessComplete => SecurityEventKind::MarketClose,
+                    SecurityEventType::Unknown(_) => return None,
+                };
+                (
+                    "securityEvent",
+                    DeepData::SecurityEvent(DeepSecurityEvent {
+                        security_event,
+                        timestamp: millis(event.timestamp),
+                        extra: Map::new(),
+                    }),
+                )
+            }
+            DeepFeedMessage::OfficialPrice(ref price) => {
+                let price_type = match price.price_type {
+                    OfficialPriceType::Opening => "Open",
+                    OfficialPriceType::Closing => "Close",
+                    OfficialPriceType::Unknown(_) => return None,
+                };
+                (
+                    "officialPrice",
+                    DeepData::OfficialPrice(DeepOfficialPrice {
+                        price_type: price_type.to_string(),
+                        price: price.price,
+                        timestamp: millis(price.timestamp),
+                        extra: Map::new(),
+                    }),
+                )
+            }
+            DeepFeedMessage::AuctionInformation(ref auction) => {
+                let auction_type = match auction.auction_type {
+                    AuctionType::Opening => "Open",
+                    AuctionType::Closing => "Close",
+                    AuctionType::Ipo => "IPO",
+                    AuctionType::Halt => "Halt",
+                    AuctionType::Volatility => "Volatility",
+                    AuctionType::Unknown(_) => return None,
+                };
+                let imbalance_side = match auction.imbalance_side {
+                    ImbalanceSide::Buy => "Buy",
+                    ImbalanceSide::Sell => "Sell",
+                    ImbalanceSide::NoImbalance | ImbalanceSide::Unknown(_) => "No Imbalance",
+                };
+                (
+                    "auction",
+                    DeepData::Auction(DeepAuction {
+                        auction_type: auction_type.to_string(),
+                        paired_shares: u64::from(auction.paired_shares),
+                        imbalance_shares: u64::from(auction.imbalance_shares),
+                        imbalance_side: imbalance_side.to_string(),
+                        reference_price: auction.reference_price,
+                        indicative_price: auction.indicative_clearing_price,
+                        auction_book_price: auction.auction_book_clearing_price,
+                        collar_reference_price: auction.collar_reference_price,
+                        lower_collar_price: auction.lower_auction_collar,
+                        upper_collar_price: auction.upper_auction_collar,
+                        extension_number: u64::from(auction.extension_number),
+                        start_time: eastern_time_of_day(auction.scheduled_auction_time),
+                        last_update: millis(auction.timestamp),
+                        extra: Map::new(),
+                    }),
+                )
+            }
+            DeepFeedMessage::SecurityDirectory(_) | DeepFeedMessage::Unknown { .. } => return None,
+        };
+        Some(Event::Deep(DeepMessage {
+            symbol,
+            message_type: message_type.to_string(),
+            data,
+            seq: Some(seq),
+            extra: Map::new(),
+        }))
+    }
+}
+
+#[cfg(feature = "pcap")]
+fn latest_tops<'a>(tops: &'a mut HashMap<String, Tops>, symbol: &str) -> &'a mut Tops {
+    tops.entry(symbol.to_string()).or_insert_with(|| Tops {
+        symbol: symbol.to_string(),
+        sector: None,
+        security_type: None,
+        bid_price: Money::default(),
+        bid_size: 0,
+        ask_price: Money::default(),
+        ask_size: 0,
+        last_updated: 0,
+        last_sale_price: Money::default(),
+        last_sale_size: 0,
+        last_sale_time: 0,
+        volume: 0,
+        market_percent: None,
+        seq: None,
+        extra: Map::new(),
+    })
+}
+
+/// Formats `secs` since the epoch as a time of day in US Eastern time, as
+/// the stream gives auction start times. Daylight saving time runs from 2am
+/// on the second Sunday in March to 2am on the first Sunday in November.
+#[cfg(feature = "pcap")]
+fn eastern_time_of_day(secs: u32) -> String {
+    let utc = match Utc.timestamp_opt(i64::from(secs), 0).single() {
+        Some(utc) => utc.naive_utc(),
+        None => return String::new(),
+    };
+    let sunday = |month, nth: u32| {
+        let first = NaiveDate::from_ymd_opt(utc.year(), month, 1).unwrap_or_default();
+        let days = (7 - first.weekday().num_days_from_sunday()) % 7 + 7 * (nth - 1);
+        first + chrono::Duration::days(i64::from(days))
+    };
+    // At 2am local time, in UTC.
+    let summer_starts = sunday(3, 2).and_hms_opt(7, 0, 0).unwrap_or_default();
+    let summer_ends = sunday(11, 1).and_hms_opt(6, 0, 0).unwrap_or_default();
+    let offset = if utc >= summer_starts && utc < summer_ends {
+        4
+    } else {
+        5
+    };
+    (utc - chrono::Duration::hours(offset))
+        .format("%H:%M:%S")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::LastSale;
+
+    fn sale(symbol: &str, time: u64) -> Result<Event> {
+        Ok(Event::Last(LastSale {
+            symbol: symbol.to_string(),
+            price: Default::default(),
+            size: 100,
+            time,
+            seq: None,
+            extra: Default::default(),
+        }))
+    }
+
+    fn sales(symbols: &[(&str, u64)]) -> Vec<Result<Event>> {
+        symbols.iter().map(|&(symbol, time)| sale(symbol, time)).collect()
+    }
+
+    fn times(replay: Replay) -> Vec<(String, u64)> {
+        replay
+            .map(|event| {
+                let event = event.unwrap();
+                (event.symbol().unwrap().to_string(), event.timestamp().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filters_by_time_and_symbol() {
+        let events = sales(&[("SPY", 1), ("AAPL", 2), ("spy", 3), ("SPY", 4), ("SPY", 5)]);
+        let replay = Replay::new(events.into_iter())
+            .with_speed(Speed::AsFastAsPossible)
+            .starting_at(2)
+            .ending_at(4)
+            .with_symbols(&["SPY"]);
+        assert_eq!(
+            times(replay),
+            vec![("spy".to_string(), 3), ("SPY".to_string(), 4)]
+        );
+    }
+
+    #[test]
+    fn clock_follows_events() {
+        let mut replay = Replay::new(sales(&[("SPY", 1000), ("SPY", 2000)]).into_iter())
+            .with_speed(Speed::AsFastAsPossible);
+        let clock = replay.clock();
+        assert_eq!(clock.now(), None);
+        replay.next();
+        assert_eq!(clock.now(), Some(1000));
+        replay.next();
+        assert_eq!(clock.now(), Some(2000));
+    }
+
+    #[test]
+    fn speed_scales_waits() {
+        let events = sales(&[("SPY", 1000), ("SPY", 1200), ("SPY", 1400)]);
+        let began = time::Instant::now();
+        let replay = Replay::new(events.into_iter()).with_speed(Speed::Multiplier(10.0));
+        assert_eq!(replay.count(), 3);
+        // Only a lower bound: loaded machines can sleep for longer.
+        assert!(began.elapsed() >= time::Duration::from_millis(40));
+        assert_eq!(
+            scaled(time::Duration::from_secs(3), 10.0),
+            time::Duration::from_millis(300)
+        );
+    }
+
+    #[test]
+    fn tiny_speeds_saturate() {
+        let second = time::Duration::from_secs(1);
+        assert_eq!(scaled(second, 4.0), time::Duration::from_millis(250));
+        assert_eq!(scaled(second, 1e-300), time::Duration::MAX);
+        assert_eq!(scaled(time::Duration::from_secs(0), 1e-300), time::Duration::from_secs(0));
+    }
+
+    #[cfg(feature = "pcap")]
+    #[test]
+    fn auction_times_are_eastern() {
+        // 2017-10-31 20:00 UTC, in daylight saving time.
+        assert_eq!(eastern_time_of_day(1_509_480_000), "16:00:00");
+        // 2018-01-03 14:30 UTC.
+        assert_eq!(eastern_time_of_day(1_514_989_800), "09:30:00");
+        // 2018-03-11, either side of 2am.
+        assert_eq!(eastern_time_of_day(1_520_751_540), "01:59:00");
+        assert_eq!(eastern_time_of_day(1_520_751_600), "03:00:00");
+    }
+}
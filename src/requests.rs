@@ -0,0 +1,382 @@
+//! Validated builders for endpoints that take several parameters.
+use std::error;
+use std::fmt;
+
+use endpoints::{ChartParam, Duration, StocksEndpoint};
+#[cfg(feature = "iex-cloud")]
+use endpoints::{percent_encode, DataEndpoint};
+#[cfg(feature = "iex-cloud")]
+use std::borrow::Cow;
+#[cfg(feature = "iex-cloud")]
+use Endpoint;
+
+/// `BuildError` is returned by request builders given parameters IEX would
+/// reject.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BuildError {
+    pub reason: String,
+}
+
+impl BuildError {
+    fn new<S: Into<String>>(reason: S) -> BuildError {
+        BuildError {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid request: {}", self.reason)
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// `ChartRequest` is a validated set of chart parameters. Build one with
+/// `ChartRequest::builder()` and turn it into a `StocksEndpoint::Chart` with
+/// `into()`.
+#[derive(Builder, Clone, Copy, Debug, PartialEq, Eq)]
+#[builder(build_fn(skip))]
+pub struct ChartRequest<'a> {
+    /// The chart range. Defaults to one month.
+    #[builder(default)]
+    range: Duration<'a>,
+    /// Reset the 1d chart at midnight instead of 9:30am ET.
+    #[builder(setter(into), default)]
+    reset: Option<bool>,
+    /// Simplify the chart with the Douglas-Peucker algorithm.
+    #[builder(setter(into), default)]
+    simplify: Option<bool>,
+    /// Return every Nth data point.
+    #[builder(setter(into), default)]
+    interval: Option<usize>,
+    /// Make changeOverTime relative to the previous day's close.
+    #[builder(setter(into), default)]
+    change_from_close: Option<bool>,
+    /// Return only the last N data points.
+    #[builder(setter(into), default)]
+    last: Option<usize>,
+}
+
+impl<'a> ChartRequest<'a> {
+    pub fn builder() -> ChartRequestBuilder<'a> {
+        ChartRequestBuilder::default()
+    }
+
+    pub fn range(&self) -> Duration<'a> {
+        self.range
+    }
+
+    /// Returns the chart parameters in the order IEX documents them.
+    pub fn params(&self) -> Vec<ChartParam> {
+        let mut params = Vec::new();
+        if let Some(reset) = self.reset {
+            params.push(ChartParam::Reset(reset));
+        }
+        if let Some(simplify) = self.simplify {
+            params.push(ChartParam::Simplify(simplify));
+        }
+        if let Some(interval) = self.interval {
+            params.push(ChartParam::Interval(interval));
+        }
+        if let Some(change_from_close) = self.change_from_close {
+            params.push(ChartParam::ChangeFromClose(change_from_close));
+        }
+        if let Some(last) = self.last {
+            params.push(ChartParam::Last(last));
+        }
+        params
+    }
+}
+
+impl<'a> ChartRequestBuilder<'a> {
+    pub fn build(&self) -> Result<ChartRequest<'a>, BuildError> {
+        self.validate()?;
+        Ok(ChartRequest {
+            range: self.range.unwrap_or_default(),
+            reset: self.reset.unwrap_or_default(),
+            simplify: self.simplify.unwrap_or_default(),
+            interval: self.interval.unwrap_or_default(),
+            change_from_close: self.change_from_close.unwrap_or_default(),
+            last: self.last.unwrap_or_default(),
+        })
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        let range = self.range.unwrap_or_default();
+        match range {
+            Duration::None => return Err(BuildError::new("chart range must not be empty")),
+            Duration::OneDay | Duration::Date(_) => {}
+            _ => {
+                if self.reset.is_some_and(|reset| reset.is_some()) {
+                    return Err(BuildError::new(format!(
+                        "chartReset only applies to 1d charts, not {}",
+                        range.to_string()
+                    )));
+                }
+            }
+        }
+        if let Some(Some(0)) = self.interval {
+            return Err(BuildError::new("chartInterval must be at least 1"));
+        }
+        if let Some(Some(0)) = self.last {
+            return Err(BuildError::new("chartLast must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<ChartRequest<'a>> for StocksEndpoint<'a> {
+    fn from(request: ChartRequest<'a>) -> StocksEndpoint<'a> {
+        let params = request.params();
+        StocksEndpoint::Chart {
+            duration: request.range,
+            params: if params.is_empty() { None } else { Some(params) },
+        }
+    }
+}
+
+/// `TimeSeriesQuery` selects rows from an IEX Cloud time series dataset,
+/// e.g. `REPORTED_FINANCIALS` for `AAPL` filings of type `10-Q`. Rows can be
+/// limited with either a `range` such as `1y` or `last-quarter`, or with
+/// `from` and `to` dates (`YYYY-MM-DD`).
+#[cfg(feature = "iex-cloud")]
+#[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[builder(build_fn(skip))]
+pub struct TimeSeriesQuery<'a> {
+    /// The dataset id.
+    #[builder(setter(into))]
+    id: Cow<'a, str>,
+    #[builder(default, setter(private, prefix = "raw"))]
+    key: Option<Cow<'a, str>>,
+    #[builder(default, setter(private, prefix = "raw"))]
+    subkey: Option<Cow<'a, str>>,
+    #[builder(default, setter(private, prefix = "raw"))]
+    range: Option<Cow<'a, str>>,
+    #[builder(default, setter(private, prefix = "raw"))]
+    from: Option<Cow<'a, str>>,
+    #[builder(default, setter(private, prefix = "raw"))]
+    to: Option<Cow<'a, str>>,
+    /// Return only the last N rows.
+    #[builder(setter(into), default)]
+    last: Option<usize>,
+    /// Treat `range` as calendar time, e.g. `next-week`, instead of reporting
+    /// periods.
+    #[builder(setter(into), default)]
+    calendar: Option<bool>,
+}
+
+#[cfg(feature = "iex-cloud")]
+impl<'a> TimeSeriesQuery<'a> {
+    pub fn builder() -> TimeSeriesQueryBuilder<'a> {
+        TimeSeriesQueryBuilder::default()
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[cfg(feature = "iex-cloud")]
+impl<'a> TimeSeriesQueryBuilder<'a> {
+    /// The dataset key, usually a symbol.
+    pub fn key<S: Into<Cow<'a, str>>>(&mut self, key: S) -> &mut Self {
+        self.raw_key(Some(key.into()))
+    }
+
+    /// The dataset subkey, e.g. a filing type. Requires a key.
+    pub fn subkey<S: Into<Cow<'a, str>>>(&mut self, subkey: S) -> &mut Self {
+        self.raw_subkey(Some(subkey.into()))
+    }
+
+    pub fn range<S: Into<Cow<'a, str>>>(&mut self, range: S) -> &mut Self {
+        self.raw_range(Some(range.into()))
+    }
+
+    pub fn from<S: Into<Cow<'a, str>>>(&mut self, from: S) -> &mut Self {
+        self.raw_from(Some(from.into()))
+    }
+
+    pub fn to<S: Into<Cow<'a, str>>>(&mut self, to: S) -> &mut Self {
+        self.raw_to(Some(to.into()))
+    }
+
+    pub fn build(&self) -> Result<TimeSeriesQuery<'a>, BuildError> {
+        self.validate()?;
+        Ok(TimeSeriesQuery {
+            id: self
+                .id
+                .clone()
+                .ok_or_else(|| BuildError::new("time series id must be set"))?,
+            key: self.key.clone().unwrap_or_default(),
+            subkey: self.subkey.clone().unwrap_or_default(),
+            range: self.range.clone().unwrap_or_default(),
+            from: self.from.clone().unwrap_or_default(),
+            to: self.to.clone().unwrap_or_default(),
+            last: self.last.unwrap_or_default(),
+            calendar: self.calendar.unwrap_or_default(),
+        })
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        let set = |field: &Option<Option<Cow<str>>>| field.as_ref().is_some_and(Option::is_some);
+        if self.id.as_ref().is_some_and(|id| id.is_empty()) {
+            return Err(BuildError::new("time series id must not be empty"));
+        }
+        if set(&self.subkey) && !set(&self.key) {
+            return Err(BuildError::new("a time series subkey requires a key"));
+        }
+        if set(&self.range) && (set(&self.from) || set(&self.to)) {
+            return Err(BuildError::new("use either range or from/to, not both"));
+        }
+        if let Some(Some(0)) = self.last {
+            return Err(BuildError::new("last must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "iex-cloud")]
+impl<'a> Endpoint for TimeSeriesQuery<'a> {
+    fn to_endpoint(self) -> String {
+        let mut endpoint = format!("time-series/{}", percent_encode(&self.id));
+        for segment in self.key.iter().chain(self.subkey.iter()) {
+            endpoint.push('/');
+            endpoint.push_str(&percent_encode(segment));
+        }
+
+        let mut params = Vec::new();
+        if let Some(range) = self.range {
+            params.push(format!("range={}", percent_encode(&range)));
+        }
+        if let Some(from) = self.from {
+            params.push(format!("from={}", percent_encode(&from)));
+        }
+        if let Some(to) = self.to {
+            params.push(format!("to={}", percent_encode(&to)));
+        }
+        if let Some(last) = self.last {
+            params.push(format!("last={}", last));
+        }
+        if let Some(calendar) = self.calendar {
+            params.push(format!("calendar={}", calendar));
+        }
+        if !params.is_empty() {
+            endpoint.push('?');
+            endpoint.push_str(&params.join("&"));
+        }
+        endpoint
+    }
+}
+
+#[cfg(feature = "iex-cloud")]
+impl<'a> From<TimeSeriesQuery<'a>> for DataEndpoint<'a> {
+    fn from(query: TimeSeriesQuery<'a>) -> DataEndpoint<'a> {
+        DataEndpoint::TimeSeries(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chart_request_builds_endpoint() {
+        let endpoint: StocksEndpoint = ChartRequest::builder()
+            .range(Duration::OneDay)
+            .reset(true)
+            .last(30)
+            .build()
+            .unwrap()
+            .into();
+        assert_eq!(
+            endpoint,
+            StocksEndpoint::Chart {
+                duration: Duration::OneDay,
+                params: Some(vec![ChartParam::Reset(true), ChartParam::Last(30)]),
+            }
+        );
+    }
+
+    #[test]
+    fn chart_request_defaults_to_one_month_without_params() {
+        let endpoint: StocksEndpoint = ChartRequest::builder().build().unwrap().into();
+        assert_eq!(
+            endpoint,
+            StocksEndpoint::Chart {
+                duration: Duration::OneMonth,
+                params: None,
+            }
+        );
+    }
+
+    #[test]
+    fn chart_request_rejects_invalid_combinations() {
+        assert!(
+            ChartRequest::builder()
+                .range(Duration::OneYear)
+                .reset(true)
+                .build()
+                .is_err()
+        );
+        assert!(ChartRequest::builder().reset(false).build().is_err());
+        assert!(ChartRequest::builder().interval(0).build().is_err());
+        assert!(ChartRequest::builder().last(0).build().is_err());
+        assert!(ChartRequest::builder().range(Duration::None).build().is_err());
+    }
+
+    #[test]
+    fn chart_request_errors_convert_into_crate_errors() {
+        fn chart(last: usize) -> ::Result<StocksEndpoint<'static>> {
+            Ok(ChartRequest::builder().last(last).build()?.into())
+        }
+        assert!(chart(5).is_ok());
+        let err = chart(0).unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::new("chartLast must be at least 1")
+        );
+    }
+
+    #[cfg(feature = "iex-cloud")]
+    #[test]
+    fn time_series_query_builds_path() {
+        let query = TimeSeriesQuery::builder()
+            .id("REPORTED_FINANCIALS")
+            .key("AAPL")
+            .subkey("10-Q")
+            .last(2)
+            .build()
+            .unwrap();
+        assert_eq!(query.to_endpoint(), "time-series/REPORTED_FINANCIALS/AAPL/10-Q?last=2");
+
+        let query = TimeSeriesQuery::builder()
+            .id("PREMIUM_WALLSTREETHORIZON_UPCOMING_EARNINGS")
+            .range("next-week")
+            .calendar(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.to_endpoint(),
+            "time-series/PREMIUM_WALLSTREETHORIZON_UPCOMING_EARNINGS?range=next-week&calendar=true"
+        );
+    }
+
+    #[cfg(feature = "iex-cloud")]
+    #[test]
+    fn time_series_query_rejects_invalid_combinations() {
+        assert!(TimeSeriesQuery::builder().build().is_err());
+        assert!(TimeSeriesQuery::builder().id("").build().is_err());
+        assert!(TimeSeriesQuery::builder().id("X").subkey("10-Q").build().is_err());
+        assert!(
+            TimeSeriesQuery::builder()
+                .id("X")
+                .range("1y")
+                .from("2019-01-01")
+                .build()
+                .is_err()
+        );
+        assert!(TimeSeriesQuery::builder().id("X").last(0).build().is_err());
+    }
+}
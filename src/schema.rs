@@ -0,0 +1,171 @@
+//! Detects drift between IEX payloads and the types in this crate.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::collections::BTreeSet;
+use std::error;
+use std::fmt;
+
+use Result;
+
+/// `SchemaReport` lists the differences between a payload and the fields a
+/// type models. Paths use JSON names, with `[]` marking array elements, e.g.
+/// `bids[].orderId`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    /// Fields in the payload that the type does not model.
+    pub unknown: Vec<String>,
+    /// Fields the type models that are absent from the payload.
+    pub missing: Vec<String>,
+}
+
+impl SchemaReport {
+    /// Returns true if the payload matches the type exactly.
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Compares `payload` against the fields modelled by `T`.
+///
+/// Fails only if `payload` doesn't deserialize into `T` at all.
+pub fn schema_report<T>(payload: &Value) -> Result<SchemaReport>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let parsed: T = serde_json::from_value(payload.clone())?;
+    let modelled = serde_json::to_value(&parsed)?;
+
+    let mut unknown = BTreeSet::new();
+    let mut missing = BTreeSet::new();
+    diff("", payload, &modelled, &mut unknown, &mut missing);
+
+    Ok(SchemaReport {
+        unknown: unknown.into_iter().collect(),
+        missing: missing.into_iter().collect(),
+    })
+}
+
+fn diff(
+    path: &str,
+    payload: &Value,
+    modelled: &Value,
+    unknown: &mut BTreeSet<String>,
+    missing: &mut BTreeSet<String>,
+) {
+    match (payload, modelled) {
+        (Value::Object(payload), Value::Object(modelled)) => {
+            for (key, value) in payload {
+                let field = join(path, key);
+                match modelled.get(key) {
+                    Some(model) => diff(&field, value, model, unknown, missing),
+                    None => {
+                        unknown.insert(field);
+                    }
+                }
+            }
+            for key in modelled.keys().filter(|key| !payload.contains_key(*key)) {
+                missing.insert(join(path, key));
+            }
+        }
+        (Value::Array(payload), Value::Array(modelled)) => {
+            let elements = format!("{}[]", path);
+            for (value, model) in payload.iter().zip(modelled) {
+                diff(&elements, value, model, unknown, missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// `UnknownFields` is returned by strict deserialization when the payload
+/// contains fields the target type does not model.
+#[derive(Debug)]
+pub struct UnknownFields {
+    pub fields: Vec<String>,
+}
+
+impl fmt::Display for UnknownFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown fields in response: {}", self.fields.join(", "))
+    }
+}
+
+impl error::Error for UnknownFields {}
+
+/// Deserializes `payload` into `T`, failing with `UnknownFields` if the payload
+/// has any field that `T` does not model.
+pub fn from_value_strict<T>(payload: Value) -> Result<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let report = schema_report::<T>(&payload)?;
+    if !report.unknown.is_empty() {
+        return Err(UnknownFields {
+            fields: report.unknown,
+        }.into());
+    }
+    Ok(serde_json::from_value(payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{Book, Logo};
+
+    fn book() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/book.json")).unwrap()
+    }
+
+    #[test]
+    fn modelled_payload_has_empty_report() {
+        assert!(schema_report::<Book>(&book()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_and_missing_fields() {
+        let mut payload = book();
+        payload["quote"]["iexOpen"] = json!(171.2);
+        payload["bids"][1]["orderId"] = json!(17);
+        payload["quote"]
+            .as_object_mut()
+            .unwrap()
+            .remove("peRatio");
+
+        let report = schema_report::<Book>(&payload).unwrap();
+        assert_eq!(report.unknown, vec!["bids[].orderId", "quote.iexOpen"]);
+        assert_eq!(report.missing, vec!["quote.peRatio"]);
+    }
+
+    #[test]
+    fn unknown_fields_are_kept_in_extra() {
+        let logo: Logo = serde_json::from_value(json!({
+            "url": "https://storage.googleapis.com/iex/api/logos/AAPL.png",
+            "format": "png",
+        })).unwrap();
+        assert_eq!(logo.extra["format"], "png");
+        assert_eq!(
+            serde_json::to_value(&logo).unwrap(),
+            json!({"url": "https://storage.googleapis.com/iex/api/logos/AAPL.png"})
+        );
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_fields() {
+        let mut payload = book();
+        assert!(from_value_strict::<Book>(payload.clone()).is_ok());
+
+        payload["systemEvent"]["sequence"] = json!(1);
+        let err = from_value_strict::<Book>(payload).unwrap_err();
+        let err = err.downcast::<UnknownFields>().unwrap();
+        assert_eq!(err.fields, vec!["systemEvent.sequence"]);
+    }
+}
@@ -0,0 +1,667 @@
+//! Real-time data from IEX Cloud's Server-Sent Events streams.
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::io::BufRead;
+use std::result;
+#[cfg(feature = "iex-cloud")]
+use std::io::BufReader;
+#[cfg(feature = "iex-cloud")]
+use std::time;
+
+#[cfg(feature = "async")]
+use futures::sync::mpsc;
+#[cfg(feature = "async")]
+use futures::{Async, Poll};
+#[cfg(feature = "iex-cloud")]
+use reqwest::header::{qitem, Accept};
+#[cfg(feature = "iex-cloud")]
+use reqwest::mime;
+use serde::de::{DeserializeOwned, Error as DeError};
+use serde::{Deserialize, Deserializer};
+use serde_json::{self, Map, Value};
+
+use de;
+#[cfg(feature = "iex-cloud")]
+use endpoints::percent_encode;
+use subscription::Gap;
+use types::{
+    DeepAuction, DeepBook, DeepOfficialPrice, DeepOpHaltStatus, DeepSecurityEvent, DeepSsr,
+    DeepTradingStatus, LastSale, News, Quote, SystemEvent, Tops, Trade,
+};
+#[cfg(feature = "iex-cloud")]
+use Client;
+use Result;
+
+/// The streams offered by IEX Cloud.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Quotes as they change.
+    Quotes,
+    /// Quotes, at most once a second per symbol.
+    Quotes1Second,
+    /// Quotes, at most once every five seconds per symbol.
+    Quotes5Second,
+    /// Quotes, at most once a minute per symbol.
+    Quotes1Minute,
+    Tops,
+    Last,
+    Deep,
+    News,
+}
+
+impl Channel {
+    /// Returns the path of the channel's endpoint.
+    pub fn path(&self) -> &'static str {
+        match *self {
+            Channel::Quotes => "stocksUS",
+            Channel::Quotes1Second => "stocksUS1Second",
+            Channel::Quotes5Second => "stocksUS5Second",
+            Channel::Quotes1Minute => "stocksUS1Minute",
+            Channel::Tops => "tops",
+            Channel::Last => "last",
+            Channel::Deep => "deep",
+            Channel::News => "news-stream",
+        }
+    }
+}
+
+/// `Event` is one update from a stream.
+#[derive(Debug)]
+pub enum Event {
+    Quote(Box<Quote>),
+    Tops(Tops),
+    Last(LastSale),
+    News(News),
+    /// One trade of a DEEP `trades` message.
+    Trade {
+        symbol: String,
+        trade: Trade,
+        seq: Option<u64>,
+    },
+    /// A DEEP `systemEvent` message.
+    SystemEvent {
+        symbol: String,
+        event: SystemEvent,
+        seq: Option<u64>,
+    },
+    /// Any other DEEP message, e.g. `book` or `tradingStatus`.
+    Deep(DeepMessage),
+    /// Data a `Subscription` may have missed.
+    Gap(Gap),
+}
+
+impl Event {
+    /// Returns the symbol the event is about, if it is about one.
+    pub fn symbol(&self) -> Option<&str> {
+        match *self {
+            Event::Quote(ref quote) => Some(&quote.symbol),
+            Event::Tops(ref tops) => Some(&tops.symbol),
+            Event::Last(ref last) => Some(&last.symbol),
+            Event::News(_) => None,
+            Event::Trade { ref symbol, .. } | Event::SystemEvent { ref symbol, .. } => Some(symbol),
+            Event::Deep(ref message) => Some(&message.symbol),
+            Event::Gap(ref gap) => Some(&gap.symbol),
+        }
+    }
+
+    /// Returns when the event happened, in milliseconds since the epoch.
+    pub fn timestamp(&self) -> Option<u64> {
+        match *self {
+            Event::Quote(ref quote) => Some(quote.latest_update),
+            Event::Tops(ref tops) => Some(tops.last_updated),
+            Event::Last(ref last) => Some(last.time),
+            Event::News(ref news) => Some(news.datetime.timestamp_millis() as u64),
+            Event::Trade { ref trade, .. } => Some(trade.timestamp),
+            Event::SystemEvent { ref event, .. } => Some(event.timestamp),
+            Event::Deep(ref message) => message.data.timestamp(),
+            Event::Gap(_) => None,
+        }
+    }
+
+    /// Returns IEX's sequence number for the event's message, if it has one.
+    /// Trades from the same DEEP message share a sequence number.
+    pub fn seq(&self) -> Option<u64> {
+        match *self {
+            Event::Tops(ref tops) => tops.seq,
+            Event::Last(ref last) => last.seq,
+            Event::Trade { seq, .. } | Event::SystemEvent { seq, .. } => seq,
+            Event::Deep(ref message) => message.seq,
+            Event::Quote(_) | Event::News(_) | Event::Gap(_) => None,
+        }
+    }
+}
+
+/// A message on the DEEP stream. `data` depends on `message_type`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Debug, Clone)]
+pub struct DeepMessage {
+    pub symbol: String,
+    pub message_type: String,
+    pub data: DeepData,
+    pub seq: Option<u64>,
+    #[serde(skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl<'de> Deserialize<'de> for DeepMessage {
+    fn deserialize<D>(deserializer: D) -> result::Result<DeepMessage, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawDeepMessage::deserialize(deserializer)?;
+        Ok(DeepMessage {
+            data: DeepData::parse(&raw.message_type, raw.data).map_err(DeError::custom)?,
+            symbol: raw.symbol,
+            message_type: raw.message_type,
+            seq: raw.seq,
+            extra: raw.extra,
+        })
+    }
+}
+
+/// A DEEP message before its data is parsed by type.
+#[serde(rename_all = "camelCase")]
+#[derive(Deserialize)]
+struct RawDeepMessage {
+    symbol: String,
+    message_type: String,
+    data: Value,
+    #[serde(default, deserialize_with = "de::option_number")]
+    seq: Option<u64>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+/// The data of a DEEP message, by `messageType`. Trades and system events
+/// become `Event::Trade` and `Event::SystemEvent` instead.
+#[serde(untagged)]
+#[derive(Serialize, Debug, Clone)]
+pub enum DeepData {
+    Book(DeepBook),
+    TradingStatus(DeepTradingStatus),
+    /// The trade that was broken.
+    TradeBreak(Trade),
+    OpHaltStatus(DeepOpHaltStatus),
+    Ssr(DeepSsr),
+    SecurityEvent(DeepSecurityEvent),
+    OfficialPrice(DeepOfficialPrice),
+    Auction(DeepAuction),
+    /// The data of a message type not (yet) known to this crate.
+    Unknown(Value),
+}
+
+impl DeepData {
+    /// Parses the data of a message of `message_type`.
+    pub fn parse(message_type: &str, data: Value) -> Result<DeepData> {
+        Ok(match message_type {
+            "book" => DeepData::Book(serde_json::from_value(data)?),
+            "tradingStatus" => DeepData::TradingStatus(serde_json::from_value(data)?),
+            "tradeBreak" => DeepData::TradeBreak(serde_json::from_value(data)?),
+            "opHaltStatus" => DeepData::OpHaltStatus(serde_json::from_value(data)?),
+            "ssr" => DeepData::Ssr(serde_json::from_value(data)?),
+            "securityEvent" => DeepData::SecurityEvent(serde_json::from_value(data)?),
+            "officialPrice" => DeepData::OfficialPrice(serde_json::from_value(data)?),
+            "auction" => DeepData::Auction(serde_json::from_value(data)?),
+            _ => DeepData::Unknown(data),
+        })
+    }
+
+    /// Returns when the message was sent, in milliseconds since the epoch.
+    pub fn timestamp(&self) -> Option<u64> {
+        match *self {
+            DeepData::Book(ref book) => book.timestamp,
+            DeepData::TradingStatus(ref status) => Some(status.timestamp),
+            DeepData::TradeBreak(ref trade) => Some(trade.timestamp),
+            DeepData::OpHaltStatus(ref halt) => Some(halt.timestamp),
+            DeepData::Ssr(ref ssr) => Some(ssr.timestamp),
+            DeepData::SecurityEvent(ref event) => Some(event.timestamp),
+            DeepData::OfficialPrice(ref price) => Some(price.timestamp),
+            DeepData::Auction(ref auction) => Some(auction.last_update),
+            DeepData::Unknown(ref data) => data.get("timestamp").and_then(Value::as_u64),
+        }
+    }
+}
+
+/// Decodes the data of one message on `channel`. IEX sends a JSON array of
+/// updates in each message.
+pub fn decode_events(channel: Channel, data: &str) -> Result<Vec<Event>> {
+    if data.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(match channel {
+        Channel::Quotes
+        | Channel::Quotes1Second
+        | Channel::Quotes5Second
+        | Channel::Quotes1Minute => decode(data, |quote| Event::Quote(Box::new(quote)))?,
+        Channel::Tops => decode(data, Event::Tops)?,
+        Channel::Last => decode(data, Event::Last)?,
+        Channel::News => decode(data, Event::News)?,
+        Channel::Deep => {
+            let mut events = Vec::new();
+            for message in serde_json::from_str::<Vec<RawDeepMessage>>(data)? {
+                events.extend(deep_events(message)?);
+            }
+            events
+        }
+    })
+}
+
+fn decode<T, F>(data: &str, event: F) -> Result<Vec<Event>>
+where
+    T: DeserializeOwned,
+    F: Fn(T) -> Event,
+{
+    Ok(serde_json::from_str::<Vec<T>>(data)?
+        .into_iter()
+        .map(event)
+        .collect())
+}
+
+fn deep_events(message: RawDeepMessage) -> Result<Vec<Event>> {
+    match message.message_type.as_str() {
+        "trades" => {
+            let trades: Vec<Trade> = serde_json::from_value(message.data)?;
+            let (symbol, seq) = (message.symbol, message.seq);
+            Ok(trades
+                .into_iter()
+                .map(|trade| Event::Trade {
+                    symbol: symbol.clone(),
+                    trade,
+                    seq,
+                })
+                .collect())
+        }
+        "systemEvent" => Ok(vec![Event::SystemEvent {
+            event: serde_json::from_value(message.data)?,
+            symbol: message.symbol,
+            seq: message.seq,
+        }]),
+        _ => Ok(vec![Event::Deep(DeepMessage {
+            data: DeepData::parse(&message.message_type, message.data)?,
+            symbol: message.symbol,
+            message_type: message.message_type,
+            seq: message.seq,
+            extra: message.extra,
+        })]),
+    }
+}
+
+/// A raw Server-Sent Events message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SseMessage {
+    pub event: Option<String>,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+/// `SseReader` splits a Server-Sent Events body into messages. A message cut
+/// off by the end of the body is dropped, as browsers do.
+pub struct SseReader<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> SseReader<R> {
+    pub fn new(reader: R) -> SseReader<R> {
+        SseReader {
+            reader,
+            line: String::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SseReader<R> {
+    type Item = Result<SseMessage>;
+
+    fn next(&mut self) -> Option<Result<SseMessage>> {
+        let mut message = SseMessage::default();
+        let mut has_data = false;
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+            let line = self.line.trim_end_matches('\n').trim_end_matches('\r');
+            if line.is_empty() {
+                if has_data {
+                    if message.data.ends_with('\n') {
+                        message.data.pop();
+                    }
+                    return Some(Ok(message));
+                }
+                message = SseMessage::default();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "data" => {
+                    has_data = true;
+                    message.data.push_str(value);
+                    message.data.push('\n');
+                }
+                "event" => message.event = Some(value.to_string()),
+                "id" => message.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `StreamRefused` is returned when IEX answers a stream request with an
+/// error, such as an invalid token.
+#[derive(Debug)]
+pub struct StreamRefused {
+    pub status: u16,
+}
+
+impl fmt::Display for StreamRefused {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IEX refused the stream with HTTP status {}", self.status)
+    }
+}
+
+impl error::Error for StreamRefused {}
+
+impl StreamRefused {
+    /// Returns whether trying again later might succeed: true for server
+    /// errors and rate limiting, false for other client errors.
+    pub fn is_retryable(&self) -> bool {
+        self.status == 429 || !(400..500).contains(&self.status)
+    }
+}
+
+/// `EventStream` is a blocking iterator over the events of one stream. It
+/// ends when the connection closes.
+pub struct EventStream {
+    channel: Channel,
+    messages: SseReader<Box<dyn BufRead + Send>>,
+    pending: VecDeque<Event>,
+}
+
+impl EventStream {
+    /// Creates a stream reading `channel`'s messages from any reader, such as
+    /// a saved stream.
+    pub fn new<R>(channel: Channel, reader: R) -> EventStream
+    where
+        R: BufRead + Send + 'static,
+    {
+        EventStream {
+            channel,
+            messages: SseReader::new(Box::new(reader)),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+}
+
+impl Iterator for EventStream {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            let message = match self.messages.next()? {
+                Ok(message) => message,
+                Err(e) => return Some(Err(e)),
+            };
+            match decode_events(self.channel, &message.data) {
+                Ok(events) => self.pending.extend(events),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// `AsyncEventStream` is an `EventStream` read on a background thread and
+/// delivered as a `futures::Stream`.
+#[cfg(feature = "async")]
+pub struct AsyncEventStream {
+    events: mpsc::UnboundedReceiver<Result<Event>>,
+}
+
+#[cfg(feature = "async")]
+impl EventStream {
+    /// Moves the stream to a new thread. The thread exits when the stream
+    /// ends, or at the next event after the `AsyncEventStream` is dropped.
+    pub fn into_async(self) -> AsyncEventStream {
+        let (sender, events) = mpsc::unbounded();
+        ::std::thread::spawn(move || {
+            for event in self {
+                if sender.unbounded_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        AsyncEventStream { events }
+    }
+}
+
+#[cfg(feature = "async")]
+impl ::futures::Stream for AsyncEventStream {
+    type Item = Event;
+    type Error = ::failure::Error;
+
+    fn poll(&mut self) -> Poll<Option<Event>, ::failure::Error> {
+        match self.events.poll() {
+            Ok(Async::Ready(Some(Ok(event)))) => Ok(Async::Ready(Some(event))),
+            Ok(Async::Ready(Some(Err(e)))) => Err(e),
+            Ok(Async::Ready(None)) | Err(()) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+        }
+    }
+}
+
+#[cfg(feature = "iex-cloud")]
+impl Client {
+    /// Returns the URL of `channel` for `symbols`.
+    pub fn stream_url<S>(&self, channel: Channel, symbols: &[S]) -> String
+    where
+        S: AsRef<str>,
+    {
+        let symbols: Vec<String> = symbols.iter().map(|s| percent_encode(s.as_ref())).collect();
+        let mut path = format!("{}?symbols={}", channel.path(), symbols.join(","));
+        if channel == Channel::Deep {
+            path.push_str("&channels=deep");
+        }
+        self.url_from(&self.sse_base_url, &path)
+    }
+
+    /// Opens `channel` for `symbols`.
+    pub fn stream<S>(&self, channel: Channel, symbols: &[S]) -> Result<EventStream>
+    where
+        S: AsRef<str>,
+    {
+        // Streams can be quiet for a long time, so reads must not time out.
+        let response = ::reqwest::Client::builder()
+            .timeout(None::<time::Duration>)
+            .build()?
+            .get(self.stream_url(channel, symbols).as_str())
+            .header(Accept(vec![qitem(mime::TEXT_EVENT_STREAM)]))
+            .send()?;
+        if !response.status().is_success() {
+            return Err(StreamRefused {
+                status: response.status().as_u16(),
+            }.into());
+        }
+        Ok(EventStream::new(channel, BufReader::new(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::SecurityEventKind;
+
+    fn messages(body: &str) -> Vec<SseMessage> {
+        SseReader::new(body.as_bytes())
+            .map(|message| message.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reader_splits_messages_on_blank_lines() {
+        let body = concat!(
+            ": comment\n\n",
+            "event: quote\ndata: [1,\ndata:2]\nid: 7\n\r\n",
+            "data: []\n\n",
+            "data: [3]",
+        );
+        assert_eq!(
+            messages(body),
+            vec![
+                SseMessage {
+                    event: Some("quote".to_string()),
+                    data: "[1,\n2]".to_string(),
+                    id: Some("7".to_string()),
+                },
+                SseMessage {
+                    event: None,
+                    data: "[]".to_string(),
+                    id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deep_trades_and_system_events_are_typed() {
+        let data = json!([
+            {
+                "symbol": "SNAP",
+                "messageType": "trades",
+                "data": [{
+                    "price": 15.52,
+                    "size": 100,
+                    "tradeId": 517341294,
+                    "isISO": false,
+                    "isOddLot": false,
+                    "isOutsideRegularHours": false,
+                    "isSinglePriceCross": false,
+                    "isTradeThroughExempt": false,
+                    "timestamp": 1494419730000u64,
+                }],
+                "seq": 3,
+            },
+            {
+                "symbol": "SNAP",
+                "messageType": "systemEvent",
+                "data": {"systemEvent": "R", "timestamp": 1494627600000u64},
+            },
+            {
+                "symbol": "SNAP",
+                "messageType": "tradingStatus",
+                "data": {"status": "T", "reason": "", "timestamp": 1494588017674u64},
+            },
+        ]).to_string();
+
+        let events = decode_events(Channel::Deep, &data).unwrap();
+        match events[0] {
+            Event::Trade {
+                ref symbol,
+                ref trade,
+                seq,
+            } => {
+                assert_eq!(symbol, "SNAP");
+                assert_eq!(trade.size, 100);
+                assert_eq!(seq, Some(3));
+            }
+            ref event => panic!("expected a trade, got {:?}", event),
+        }
+        match events[1] {
+            Event::SystemEvent { ref event, .. } => {
+                assert_eq!(event.system_event, ::types::SystemEventType::StartOfRegularMarketHours)
+            }
+            ref event => panic!("expected a system event, got {:?}", event),
+        }
+        match events[2] {
+            Event::Deep(DeepMessage {
+                data: DeepData::TradingStatus(ref status),
+                ..
+            }) => assert_eq!((status.status.as_str(), status.timestamp), ("T", 1494588017674)),
+            ref event => panic!("expected a trading status, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn deep_messages_are_typed() {
+        let message = |message_type: &str, data: Value| {
+            json!({"symbol": "ZIEXT", "messageType": message_type, "data": data, "seq": 1})
+        };
+        let data = json!([
+            message("book", json!({
+                "bids": [{"price": 1.05, "size": 100, "timestamp": 1000}],
+                "asks": [],
+            })),
+            message("opHaltStatus", json!({"isHalted": false, "timestamp": 1001})),
+            message("ssr", json!({"isSSR": true, "detail": "N", "timestamp": 1002})),
+            message("securityEvent", json!({"securityEvent": "MarketOpen", "timestamp": 1003})),
+            message("officialPrice", json!({"priceType": "Open", "price": 1.05, "timestamp": 1004})),
+            message("auction", json!({
+                "auctionType": "Open",
+                "pairedShares": 3600,
+                "imbalanceShares": 600,
+                "imbalanceSide": "Sell",
+                "referencePrice": 1.05,
+                "indicativePrice": 1.05,
+                "auctionBookPrice": 1.05,
+                "collarReferencePrice": 1.05,
+                "lowerCollarPrice": 0.35,
+                "upperCollarPrice": 1.75,
+                "extensionNumber": 0,
+                "startTime": "09:30:00",
+                "lastUpdate": 1005,
+            })),
+            message("newType", json!({"timestamp": 1006})),
+        ]).to_string();
+
+        let events = decode_events(Channel::Deep, &data).unwrap();
+        let data: Vec<&DeepData> = events
+            .iter()
+            .map(|event| match *event {
+                Event::Deep(ref message) => &message.data,
+                ref event => panic!("expected a DEEP message, got {:?}", event),
+            })
+            .collect();
+        match *data[0] {
+            DeepData::Book(ref book) => assert_eq!((book.bids.len(), book.timestamp), (1, None)),
+            ref data => panic!("expected a book, got {:?}", data),
+        }
+        assert!(matches!(*data[1], DeepData::OpHaltStatus(ref halt) if !halt.is_halted));
+        assert!(matches!(*data[2], DeepData::Ssr(ref ssr) if ssr.detail == "N"));
+        assert!(matches!(
+            *data[3],
+            DeepData::SecurityEvent(ref event)
+                if event.security_event == SecurityEventKind::MarketOpen
+        ));
+        assert!(matches!(*data[4], DeepData::OfficialPrice(ref price) if price.price_type == "Open"));
+        match *data[5] {
+            DeepData::Auction(ref auction) => {
+                assert_eq!((auction.paired_shares, auction.imbalance_side.as_str()), (3600, "Sell"));
+                assert_eq!(auction.start_time, "09:30:00");
+            }
+            ref data => panic!("expected an auction, got {:?}", data),
+        }
+        assert!(matches!(*data[6], DeepData::Unknown(_)));
+        let timestamps: Vec<Option<u64>> = events.iter().map(Event::timestamp).collect();
+        assert_eq!(
+            timestamps,
+            vec![None, Some(1001), Some(1002), Some(1003), Some(1004), Some(1005), Some(1006)]
+        );
+
+        let bad = json!([message("tradingStatus", json!({"reason": "T1"}))]).to_string();
+        assert!(decode_events(Channel::Deep, &bad).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Streams that reconnect when they drop and report what may have been missed.
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time;
+
+use failure::Error;
+
+#[cfg(feature = "iex-cloud")]
+use stream::Channel;
+use stream::{Event, EventStream, StreamRefused};
+#[cfg(feature = "iex-cloud")]
+use Client;
+use Result;
+
+/// `Backoff` spaces out reconnection attempts. The first attempt waits
+/// `initial`, and each consecutive failure multiplies the delay by
+/// `multiplier`, up to `max`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: time::Duration,
+    pub max: time::Duration,
+    pub multiplier: u32,
+    /// Give up after this many consecutive failed attempts. `None` retries
+    /// forever.
+    pub attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: time::Duration::from_millis(500),
+            max: time::Duration::from_secs(30),
+            multiplier: 2,
+            attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Returns the delay before the attempt following `failures` consecutive
+    /// failures.
+    pub fn delay(&self, failures: u32) -> time::Duration {
+        let mut delay = self.initial;
+        for _ in 0..failures {
+            delay = match delay.checked_mul(self.multiplier) {
+                Some(delay) if delay < self.max => delay,
+                _ => return self.max,
+            };
+        }
+        delay.min(self.max)
+    }
+}
+
+/// Why a `Gap` was reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GapReason {
+    /// The stream dropped and was reconnected.
+    Disconnected,
+    /// IEX sequence numbers skipped ahead.
+    Sequence,
+}
+
+/// `Gap` marks data a `Subscription` may have missed for `symbol`. Fill it
+/// by fetching the symbol's data between `since` and `until` through REST.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gap {
+    pub reason: GapReason,
+    pub symbol: String,
+    /// Time of the symbol's last event before the gap, in milliseconds since
+    /// the epoch, or `None` if there was none.
+    pub since: Option<u64>,
+    /// Time of the first event after the gap, if known.
+    pub until: Option<u64>,
+    /// How many messages were skipped, if sequence numbers tell.
+    pub missed: Option<u64>,
+}
+
+/// `StreamClosed` ends a `Subscription` whose connections kept closing
+/// before delivering an event, once its `Backoff` gives up.
+#[derive(Debug)]
+pub struct StreamClosed;
+
+impl fmt::Display for StreamClosed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the stream closed before delivering an event")
+    }
+}
+
+impl error::Error for StreamClosed {}
+
+/// Opens a stream for a set of symbols.
+type Connect<'a> = Box<dyn FnMut(&[String]) -> Result<EventStream> + 'a>;
+
+/// `Subscription` is an `EventStream` that survives disconnection. When the
+/// stream drops it reconnects with `Backoff`, subscribing to the same symbols,
+/// and reports a `Gap` for each of them just before the symbol's first event
+/// on the new connection. Gaps within a connection are found from IEX
+/// sequence numbers.
+///
+/// Failed connection attempts are retried silently, and so are connections
+/// that close before delivering an event. The error is returned only when the
+/// backoff gives up or IEX refuses the stream for good, after which the
+/// subscription ends.
+pub struct Subscription<'a> {
+    connect: Connect<'a>,
+    symbols: Vec<String>,
+    backoff: Backoff,
+    stream: Option<EventStream>,
+    /// Whether any connection has delivered an event.
+    connected: bool,
+    /// Whether the current connection has delivered an event.
+    delivered: bool,
+    reconnecting: bool,
+    failures: u32,
+    done: bool,
+    /// The error to return once `pending` is drained.
+    error: Option<Error>,
+    last_seen: HashMap<String, u64>,
+    sequences: HashMap<(String, String), u64>,
+    /// Disconnection gaps waiting for their symbol's next event.
+    outages: Vec<Gap>,
+    pending: VecDeque<Event>,
+}
+
+impl<'a> Subscription<'a> {
+    /// Creates a subscription from any function that opens a stream for a set
+    /// of symbols. Nothing is opened until the first call to `next`.
+    pub fn new<F>(symbols: Vec<String>, backoff: Backoff, connect: F) -> Subscription<'a>
+    where
+        F: FnMut(&[String]) -> Result<EventStream> + 'a,
+    {
+        Subscription {
+            connect: Box::new(connect),
+            symbols,
+            backoff,
+            stream: None,
+            connected: false,
+            delivered: false,
+            reconnecting: false,
+            failures: 0,
+            done: false,
+            error: None,
+            last_seen: HashMap::new(),
+            sequences: HashMap::new(),
+            outages: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    /// Replaces the subscribed symbols, reconnecting before the next event.
+    /// Nothing was dropped, so the new connection reports no gaps.
+    pub fn resubscribe(&mut self, symbols: Vec<String>) {
+        let kept: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+        self.last_seen.retain(|symbol, _| kept.contains(symbol));
+        self.sequences.retain(|(symbol, _), _| kept.contains(symbol));
+        self.outages.retain(|gap| kept.contains(&gap.symbol));
+        self.symbols = symbols;
+        self.stream = None;
+        self.connected = false;
+        self.reconnecting = false;
+        self.failures = 0;
+    }
+
+    fn connect(&mut self) -> Result<()> {
+        loop {
+            if self.reconnecting {
+                thread::sleep(self.backoff.delay(self.failures));
+            }
+            match (self.connect)(&self.symbols) {
+                Ok(stream) => {
+                    if self.connected {
+                        self.report_disconnection();
+                    }
+                    self.stream = Some(stream);
+                    self.delivered = false;
+                    self.reconnecting = false;
+                    return Ok(());
+                }
+                Err(e) => self.fail(e)?,
+            }
+        }
+    }
+
+    /// Counts a failed attempt, returning `e` if the subscription gives up.
+    fn fail(&mut self, e: Error) -> Result<()> {
+        self.failures += 1;
+        self.reconnecting = true;
+        let refused = e
+            .downcast_ref::<StreamRefused>()
+            .is_some_and(|refused| !refused.is_retryable());
+        let exhausted = self
+            .backoff
+            .attempts
+            .is_some_and(|attempts| self.failures >= attempts);
+        if refused || exhausted {
+            self.done = true;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Handles the end of the current connection, which `e` broke if it
+    /// didn't just close.
+    fn disconnected(&mut self, e: Option<Error>) -> Result<()> {
+        self.stream = None;
+        self.reconnecting = true;
+        if self.delivered {
+            return Ok(());
+        }
+        self.fail(e.unwrap_or_else(|| StreamClosed.into()))
+    }
+
+    /// Holds a gap for each symbol until its next event, unless one is held
+    /// already from an earlier disconnection.
+    fn report_disconnection(&mut self) {
+        for symbol in &self.symbols {
+            let symbol = symbol.to_uppercase();
+            if self.outages.iter().any(|gap| gap.symbol == symbol) {
+                continue;
+            }
+            self.outages.push(Gap {
+                reason: GapReason::Disconnected,
+                since: self.last_seen.get(&symbol).cloned(),
+                until: None,
+                missed: None,
+                symbol,
+            });
+        }
+    }
+
+    fn track(&mut self, event: Event) {
+        if let Some(symbol) = event.symbol().map(str::to_uppercase) {
+            let since = self.last_seen.get(&symbol).cloned();
+            let mut skipped = None;
+            if let (Some(seq), Some(kind)) = (event.seq(), kind(&event)) {
+                let key = (symbol.clone(), kind);
+                if let Some(last) = self.sequences.insert(key, seq) {
+                    skipped = seq.checked_sub(last).and_then(|d| d.checked_sub(1));
+                }
+            }
+            match self.outages.iter().position(|gap| gap.symbol == symbol) {
+                Some(i) => {
+                    let mut gap = self.outages.remove(i);
+                    gap.until = event.timestamp();
+                    gap.missed = skipped;
+                    self.pending.push_back(Event::Gap(gap));
+                }
+                None if skipped.is_some_and(|skipped| skipped > 0) => {
+                    self.pending.push_back(Event::Gap(Gap {
+                        reason: GapReason::Sequence,
+                        symbol: symbol.clone(),
+                        since,
+                        until: event.timestamp(),
+                        missed: skipped,
+                    }));
+                }
+                None => {}
+            }
+            if let Some(timestamp) = event.timestamp() {
+                self.last_seen.insert(symbol, timestamp);
+            }
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Ends the subscription with `e`, after the gaps still held.
+    fn give_up(&mut self, e: Error) {
+        self.pending
+            .extend(self.outages.drain(..).map(Event::Gap));
+        self.error = Some(e);
+    }
+}
+
+/// Returns the message type whose sequence numbers `event` follows.
+fn kind(event: &Event) -> Option<String> {
+    match *event {
+        Event::Tops(_) => Some("tops".to_string()),
+        Event::Last(_) => Some("last".to_string()),
+        Event::Trade { .. } => Some("trades".to_string()),
+        Event::SystemEvent { .. } => Some("systemEvent".to_string()),
+        Event::Deep(ref message) => Some(message.message_type.clone()),
+        Event::Quote(_) | Event::News(_) | Event::Gap(_) => None,
+    }
+}
+
+impl<'a> Iterator for Subscription<'a> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return self.error.take().map(Err);
+            }
+            let next = match self.stream {
+                Some(ref mut stream) => stream.next(),
+                None => {
+                    if let Err(e) = self.connect() {
+                        self.give_up(e);
+                    }
+                    continue;
+                }
+            };
+            let ended = match next {
+                Some(Ok(event)) => {
+                    self.connected = true;
+                    self.delivered = true;
+                    self.failures = 0;
+                    self.track(event);
+                    continue;
+                }
+                // Read errors mean the connection broke; anything else is
+                // bad data on a working connection.
+                Some(Err(e)) => {
+                    if e.downcast_ref::<io::Error>().is_none() {
+                        return Some(Err(e));
+                    }
+                    self.disconnected(Some(e))
+                }
+                None => self.disconnected(None),
+            };
+            if let Err(e) = ended {
+                self.give_up(e);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "iex-cloud")]
+impl Client {
+    /// Subscribes to `channel` for `symbols`, reconnecting with `backoff`
+    /// whenever the stream drops.
+    pub fn subscribe<'a, S>(
+        &'a self,
+        channel: Channel,
+        symbols: &[S],
+        backoff: Backoff,
+    ) -> Subscription<'a>
+    where
+        S: AsRef<str>,
+    {
+        let symbols = symbols.iter().map(|s| s.as_ref().to_string()).collect();
+        Subscription::new(symbols, backoff, move |symbols| {
+            self.stream(channel, symbols)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stream::Channel;
+
+    #[test]
+    fn backoff_grows_to_max() {
+        let backoff = Backoff {
+            initial: time::Duration::from_millis(100),
+            max: time::Duration::from_millis(1000),
+            multiplier: 3,
+            attempts: None,
+        };
+        let delays: Vec<time::Duration> = (0..5).map(|failures| backoff.delay(failures)).collect();
+        let ms = time::Duration::from_millis;
+        assert_eq!(delays, vec![ms(100), ms(300), ms(900), ms(1000), ms(1000)]);
+        assert_eq!(backoff.delay(u32::MAX), backoff.max);
+    }
+
+    #[test]
+    fn sequence_jumps_are_gaps() {
+        let data = |seq: u64, timestamp: u64| {
+            json!([{
+                "symbol": "SNAP",
+                "messageType": "tradingStatus",
+                "data": {"status": "T", "timestamp": timestamp},
+                "seq": seq,
+            }]).to_string()
+        };
+        let body = format!(
+            "data: {}\n\ndata: {}\n\ndata: {}\n\n",
+            data(1, 1000),
+            data(2, 2000),
+            data(5, 5000)
+        );
+        let mut connected = false;
+        let subscription = Subscription::new(vec!["snap".to_string()], Backoff::default(), |_| {
+            if connected {
+                return Err(::failure::err_msg("down"));
+            }
+            connected = true;
+            Ok(EventStream::new(Channel::Deep, io::Cursor::new(body.clone())))
+        });
+
+        let gaps: Vec<Gap> = subscription
+            .take(4)
+            .filter_map(|event| match event.unwrap() {
+                Event::Gap(gap) => Some(gap),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![Gap {
+                reason: GapReason::Sequence,
+                symbol: "SNAP".to_string(),
+                since: Some(2000),
+                until: Some(5000),
+                missed: Some(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn sequence_resets_are_not_gaps() {
+        let data = |seq: u64| {
+            json!([{
+                "symbol": "SNAP",
+                "messageType": "tradingStatus",
+                "data": {"status": "T", "timestamp": 1000},
+                "seq": seq,
+            }]).to_string()
+        };
+        let body = format!("data: {}\n\ndata: {}\n\n", data(u64::MAX), data(1));
+        let mut connected = false;
+        let subscription = Subscription::new(vec!["snap".to_string()], Backoff::default(), |_| {
+            if connected {
+                return Err(::failure::err_msg("down"));
+            }
+            connected = true;
+            Ok(EventStream::new(Channel::Deep, io::Cursor::new(body.clone())))
+        });
+
+        let seqs: Vec<Option<u64>> = subscription
+            .take(2)
+            .map(|event| event.unwrap().seq())
+            .collect();
+        assert_eq!(seqs, vec![Some(u64::MAX), Some(1)]);
+    }
+
+    #[test]
+    fn resubscribing_reports_no_gaps() {
+        let data = |symbol: &str| {
+            json!([{
+                "symbol": symbol,
+                "messageType": "tradingStatus",
+                "data": {"status": "T", "timestamp": 1000},
+                "seq": 1,
+            }]).to_string()
+        };
+        let mut subscription = Subscription::new(
+            vec!["snap".to_string()],
+            Backoff::default(),
+            |symbols: &[String]| {
+                let body: String = symbols
+                    .iter()
+                    .map(|symbol| format!("data: {}\n\n", data(symbol)))
+                    .collect();
+                Ok(EventStream::new(Channel::Deep, io::Cursor::new(body)))
+            },
+        );
+
+        fn next(subscription: &mut Subscription) -> String {
+            match subscription.next().unwrap().unwrap() {
+                Event::Deep(message) => message.symbol,
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+        assert_eq!(next(&mut subscription), "snap");
+        subscription.resubscribe(vec!["aapl".to_string(), "msft".to_string()]);
+        assert_eq!(next(&mut subscription), "aapl");
+        assert_eq!(next(&mut subscription), "msft");
+        assert!(!subscription.last_seen.contains_key("SNAP"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Responses of IEX Cloud time series datasets.
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use de;
+use requests::{TimeSeriesQuery, TimeSeriesQueryBuilder};
+use {Client, Result};
+
+/// `TimeSeries` is the rows returned for a `TimeSeriesQuery`, newest first.
+/// `T` is the row type of a known `Dataset`, or `Value` for any other
+/// dataset.
+#[serde(transparent)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TimeSeries<T = Value> {
+    pub rows: Vec<T>,
+}
+
+/// `Dataset` is implemented by the row types of known time series datasets.
+pub trait Dataset: DeserializeOwned {
+    /// The dataset id, e.g. `REPORTED_FINANCIALS`.
+    const ID: &'static str;
+}
+
+impl<'a> TimeSeriesQuery<'a> {
+    /// Starts a query of the dataset whose rows are `T`.
+    pub fn dataset<T: Dataset>() -> TimeSeriesQueryBuilder<'a> {
+        let mut builder = TimeSeriesQuery::builder();
+        builder.id(T::ID);
+        builder
+    }
+}
+
+/// One filing in `REPORTED_FINANCIALS`, keyed by symbol with the form type
+/// (`10-K` or `10-Q`) as subkey. The reported XBRL facts are kept as they
+/// are, by concept name.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportedFinancials {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub key: String,
+    pub subkey: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub date: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub updated: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub form_fiscal_year: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub form_fiscal_quarter: Option<u64>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub period_start: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub period_end: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub date_filed: Option<u64>,
+    #[serde(default)]
+    pub report_link: Option<String>,
+    #[serde(default)]
+    pub adsh: Option<String>,
+    #[serde(flatten)]
+    pub facts: Map<String, Value>,
+}
+
+impl ReportedFinancials {
+    /// Returns a numeric fact, e.g. `fact("NetIncomeLoss")`.
+    pub fn fact(&self, concept: &str) -> Option<f64> {
+        self.facts.get(concept).and_then(Value::as_f64)
+    }
+}
+
+impl Dataset for ReportedFinancials {
+    const ID: &'static str = "REPORTED_FINANCIALS";
+}
+
+/// One row of `PREMIUM_WALLSTREETHORIZON_UPCOMING_EARNINGS`, keyed by
+/// symbol. Wall Street Horizon field names are all lowercase.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpcomingEarning {
+    pub symbol: String,
+    #[serde(default, rename = "companyname")]
+    pub company_name: Option<String>,
+    #[serde(default, rename = "earningsdate")]
+    pub earnings_date: Option<String>,
+    /// `BMO` (before market open), `AMC` (after market close) or `DMT`.
+    #[serde(default, rename = "timeofday")]
+    pub time_of_day: Option<String>,
+    #[serde(default, rename = "fiscalquarter", deserialize_with = "de::option_number")]
+    pub fiscal_quarter: Option<u64>,
+    #[serde(default, rename = "fiscalyear", deserialize_with = "de::option_number")]
+    pub fiscal_year: Option<u64>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub subkey: Option<String>,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub date: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub updated: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+impl Dataset for UpcomingEarning {
+    const ID: &'static str = "PREMIUM_WALLSTREETHORIZON_UPCOMING_EARNINGS";
+}
+
+impl Client {
+    /// Fetches the rows matching `query`, as the rows of a `Dataset` or as
+    /// `Value`.
+    pub fn time_series<T>(&self, query: TimeSeriesQuery) -> Result<TimeSeries<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.data_request(query.into())?.try_into()
+    }
+}
@@ -0,0 +1,469 @@
+//! TOPS 1.6, IEX's top-of-book binary feed, as carried by IEX-TP segments.
+use std::str;
+
+use serde_json::Map;
+
+use iextp::{DecodeError, Fields, Segment};
+use types::{Ask, Bid, Money, SystemEvent, SystemEventType, Trade};
+use Result;
+
+byte_enum! {
+    /// The trading status of a security.
+    pub enum TradingStatus {
+        Halted => b'H',
+        /// Halted on IEX, accepting orders.
+        OrderAcceptance => b'O',
+        /// Paused on IEX, accepting orders.
+        Paused => b'P',
+        Trading => b'T',
+    }
+}
+
+byte_enum! {
+    /// Whether IEX has halted a security for operational reasons.
+    pub enum OperationalHaltStatus {
+        Halted => b'O',
+        NotHalted => b'N',
+    }
+}
+
+byte_enum! {
+    /// Why the short sale price test status of a security was set.
+    pub enum ShortSaleDetail {
+        NoPriceTest => b' ',
+        Activated => b'A',
+        Continued => b'C',
+        Deactivated => b'D',
+        NotAvailable => b'N',
+    }
+}
+
+byte_enum! {
+    /// The Limit Up-Limit Down tier of a security.
+    pub enum LuldTier {
+        NotApplicable => 0,
+        Tier1 => 1,
+        Tier2 => 2,
+    }
+}
+
+byte_enum! {
+    /// Which official price an `OfficialPrice` message sets.
+    pub enum OfficialPriceType {
+        Opening => b'Q',
+        Closing => b'M',
+    }
+}
+
+byte_enum! {
+    pub enum AuctionType {
+        Opening => b'O',
+        Closing => b'C',
+        Ipo => b'I',
+        Halt => b'H',
+        Volatility => b'V',
+    }
+}
+
+byte_enum! {
+    /// The side of an auction's imbalance.
+    pub enum ImbalanceSide {
+        Buy => b'B',
+        Sell => b'S',
+        NoImbalance => b'N',
+    }
+}
+
+/// Marks the start and end of the messages and of market hours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SystemEventMessage {
+    pub event: SystemEventType,
+    /// Nanoseconds since the epoch, as for every binary timestamp.
+    pub timestamp: u64,
+}
+
+impl SystemEventMessage {
+    pub(crate) fn decode(fields: &mut Fields) -> Result<SystemEventMessage> {
+        let event = fields.u8()?;
+        let event = match str::from_utf8(&[event]) {
+            Ok(event) => SystemEventType::from(event),
+            Err(_) => SystemEventType::Unknown(format!("{:#04x}", event)),
+        };
+        Ok(SystemEventMessage {
+            event,
+            timestamp: fields.u64()?,
+        })
+    }
+}
+
+impl From<SystemEventMessage> for SystemEvent {
+    fn from(message: SystemEventMessage) -> SystemEvent {
+        SystemEvent {
+            system_event: message.event,
+            timestamp: millis(message.timestamp),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// Describes a security traded on IEX, sent before the start of messages.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityDirectory<'a> {
+    pub flags: u8,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub round_lot_size: u32,
+    /// The adjusted previous official closing price.
+    pub adjusted_poc_price: Money,
+    pub luld_tier: LuldTier,
+}
+
+impl<'a> SecurityDirectory<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<SecurityDirectory<'a>> {
+        Ok(SecurityDirectory {
+            flags: fields.u8()?,
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            round_lot_size: fields.u32()?,
+            adjusted_poc_price: fields.price()?,
+            luld_tier: fields.u8()?.into(),
+        })
+    }
+
+    pub fn is_test_security(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    pub fn is_when_issued(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    /// Whether the security is an exchange-traded product.
+    pub fn is_etp(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradingStatusMessage<'a> {
+    pub status: TradingStatus,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    /// The halt or pause reason code, e.g. `T1`; empty when trading.
+    pub reason: &'a str,
+}
+
+impl<'a> TradingStatusMessage<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<TradingStatusMessage<'a>> {
+        Ok(TradingStatusMessage {
+            status: fields.u8()?.into(),
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            reason: fields.text(4)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperationalHalt<'a> {
+    pub status: OperationalHaltStatus,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+}
+
+impl<'a> OperationalHalt<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<OperationalHalt<'a>> {
+        Ok(OperationalHalt {
+            status: fields.u8()?.into(),
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+        })
+    }
+}
+
+/// Whether the Reg SHO short sale price test restricts a security.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortSalePriceTest<'a> {
+    pub in_effect: bool,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub detail: ShortSaleDetail,
+}
+
+impl<'a> ShortSalePriceTest<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<ShortSalePriceTest<'a>> {
+        Ok(ShortSalePriceTest {
+            in_effect: fields.u8()? != 0,
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            detail: fields.u8()?.into(),
+        })
+    }
+}
+
+/// IEX's best bid and offer for a security.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteUpdate<'a> {
+    pub flags: u8,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub bid_size: u32,
+    pub bid_price: Money,
+    pub ask_price: Money,
+    pub ask_size: u32,
+}
+
+impl<'a> QuoteUpdate<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<QuoteUpdate<'a>> {
+        Ok(QuoteUpdate {
+            flags: fields.u8()?,
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            bid_size: fields.u32()?,
+            bid_price: fields.price()?,
+            ask_price: fields.price()?,
+            ask_size: fields.u32()?,
+        })
+    }
+
+    /// Whether the security is halted, paused or otherwise not trading.
+    pub fn is_halted(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    /// Whether the quote is from the pre- or post-market session.
+    pub fn is_outside_regular_hours(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    pub fn bid(&self) -> Bid {
+        Bid {
+            price: self.bid_price,
+            size: u64::from(self.bid_size),
+            timestamp: millis(self.timestamp),
+            extra: Map::new(),
+        }
+    }
+
+    pub fn ask(&self) -> Ask {
+        Ask {
+            price: self.ask_price,
+            size: u64::from(self.ask_size),
+            timestamp: millis(self.timestamp),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// A trade on IEX, or a trade broken by a `TradeBreak`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeReport<'a> {
+    /// Sale condition flags.
+    pub flags: u8,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub size: u32,
+    pub price: Money,
+    pub trade_id: u64,
+}
+
+impl<'a> TradeReport<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<TradeReport<'a>> {
+        Ok(TradeReport {
+            flags: fields.u8()?,
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            size: fields.u32()?,
+            price: fields.price()?,
+            trade_id: fields.u64()?,
+        })
+    }
+
+    /// Whether this was an intermarket sweep order.
+    pub fn is_iso(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    pub fn is_outside_regular_hours(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    pub fn is_odd_lot(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    pub fn is_trade_through_exempt(&self) -> bool {
+        self.flags & 0x10 != 0
+    }
+
+    pub fn is_single_price_cross(&self) -> bool {
+        self.flags & 0x08 != 0
+    }
+}
+
+impl<'a> From<TradeReport<'a>> for Trade {
+    fn from(report: TradeReport<'a>) -> Trade {
+        Trade {
+            price: report.price,
+            size: u64::from(report.size),
+            trade_id: report.trade_id,
+            is_iso: report.is_iso(),
+            is_odd_lot: report.is_odd_lot(),
+            is_outside_regular_hours: report.is_outside_regular_hours(),
+            is_single_price_cross: report.is_single_price_cross(),
+            is_trade_through_exempt: report.is_trade_through_exempt(),
+            timestamp: millis(report.timestamp),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// The official opening or closing price of a security listed on IEX.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfficialPrice<'a> {
+    pub price_type: OfficialPriceType,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub price: Money,
+}
+
+impl<'a> OfficialPrice<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<OfficialPrice<'a>> {
+        Ok(OfficialPrice {
+            price_type: fields.u8()?.into(),
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            price: fields.price()?,
+        })
+    }
+}
+
+/// The state of an IEX auction of a security listed on IEX.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuctionInformation<'a> {
+    pub auction_type: AuctionType,
+    pub timestamp: u64,
+    pub symbol: &'a str,
+    pub paired_shares: u32,
+    pub reference_price: Money,
+    pub indicative_clearing_price: Money,
+    pub imbalance_shares: u32,
+    pub imbalance_side: ImbalanceSide,
+    /// How many times the auction has been extended.
+    pub extension_number: u8,
+    /// In seconds since the epoch.
+    pub scheduled_auction_time: u32,
+    pub auction_book_clearing_price: Money,
+    pub collar_reference_price: Money,
+    pub lower_auction_collar: Money,
+    pub upper_auction_collar: Money,
+}
+
+impl<'a> AuctionInformation<'a> {
+    pub(crate) fn decode(fields: &mut Fields<'a>) -> Result<AuctionInformation<'a>> {
+        Ok(AuctionInformation {
+            auction_type: fields.u8()?.into(),
+            timestamp: fields.u64()?,
+            symbol: fields.symbol()?,
+            paired_shares: fields.u32()?,
+            reference_price: fields.price()?,
+            indicative_clearing_price: fields.price()?,
+            imbalance_shares: fields.u32()?,
+            imbalance_side: fields.u8()?.into(),
+            extension_number: fields.u8()?,
+            scheduled_auction_time: fields.u32()?,
+            auction_book_clearing_price: fields.price()?,
+            collar_reference_price: fields.price()?,
+            lower_auction_collar: fields.price()?,
+            upper_auction_collar: fields.price()?,
+        })
+    }
+}
+
+/// One TOPS message. Symbols and other text borrow from the segment.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TopsMessage<'a> {
+    SystemEvent(SystemEventMessage),
+    SecurityDirectory(SecurityDirectory<'a>),
+    TradingStatus(TradingStatusMessage<'a>),
+    OperationalHalt(OperationalHalt<'a>),
+    ShortSalePriceTest(ShortSalePriceTest<'a>),
+    QuoteUpdate(QuoteUpdate<'a>),
+    TradeReport(TradeReport<'a>),
+    OfficialPrice(OfficialPrice<'a>),
+    TradeBreak(TradeReport<'a>),
+    AuctionInformation(AuctionInformation<'a>),
+    /// A message type not (yet) known to this crate, undecoded.
+    Unknown { message_type: u8, data: &'a [u8] },
+}
+
+impl<'a> TopsMessage<'a> {
+    /// Decodes one message of a TOPS segment.
+    pub fn parse(data: &'a [u8]) -> Result<TopsMessage<'a>> {
+        let (&message_type, body) = data
+            .split_first()
+            .ok_or_else(|| DecodeError::new("empty TOPS message"))?;
+        let mut fields = Fields::new(body, "TOPS message");
+        let fields = &mut fields;
+        Ok(match message_type {
+            b'S' => TopsMessage::SystemEvent(SystemEventMessage::decode(fields)?),
+            b'D' => TopsMessage::SecurityDirectory(SecurityDirectory::decode(fields)?),
+            b'H' => TopsMessage::TradingStatus(TradingStatusMessage::decode(fields)?),
+            b'O' => TopsMessage::OperationalHalt(OperationalHalt::decode(fields)?),
+            b'P' => TopsMessage::ShortSalePriceTest(ShortSalePriceTest::decode(fields)?),
+            b'Q' => TopsMessage::QuoteUpdate(QuoteUpdate::decode(fields)?),
+            b'T' => TopsMessage::TradeReport(TradeReport::decode(fields)?),
+            b'X' => TopsMessage::OfficialPrice(OfficialPrice::decode(fields)?),
+            b'B' => TopsMessage::TradeBreak(TradeReport::decode(fields)?),
+            b'A' => TopsMessage::AuctionInformation(AuctionInformation::decode(fields)?),
+            _ => TopsMessage::Unknown {
+                message_type,
+                data: body,
+            },
+        })
+    }
+
+    /// Returns the symbol the message is about, if any.
+    pub fn symbol(&self) -> Option<&'a str> {
+        match *self {
+            TopsMessage::SecurityDirectory(ref m) => Some(m.symbol),
+            TopsMessage::TradingStatus(ref m) => Some(m.symbol),
+            TopsMessage::OperationalHalt(ref m) => Some(m.symbol),
+            TopsMessage::ShortSalePriceTest(ref m) => Some(m.symbol),
+            TopsMessage::QuoteUpdate(ref m) => Some(m.symbol),
+            TopsMessage::TradeReport(ref m) | TopsMessage::TradeBreak(ref m) => Some(m.symbol),
+            TopsMessage::OfficialPrice(ref m) => Some(m.symbol),
+            TopsMessage::AuctionInformation(ref m) => Some(m.symbol),
+            TopsMessage::SystemEvent(_) | TopsMessage::Unknown { .. } => None,
+        }
+    }
+
+    /// Returns when the message was sent, in nanoseconds since the epoch.
+    pub fn timestamp(&self) -> Option<u64> {
+        match *self {
+            TopsMessage::SystemEvent(ref m) => Some(m.timestamp),
+            TopsMessage::SecurityDirectory(ref m) => Some(m.timestamp),
+            TopsMessage::TradingStatus(ref m) => Some(m.timestamp),
+            TopsMessage::OperationalHalt(ref m) => Some(m.timestamp),
+            TopsMessage::ShortSalePriceTest(ref m) => Some(m.timestamp),
+            TopsMessage::QuoteUpdate(ref m) => Some(m.timestamp),
+            TopsMessage::TradeReport(ref m) | TopsMessage::TradeBreak(ref m) => Some(m.timestamp),
+            TopsMessage::OfficialPrice(ref m) => Some(m.timestamp),
+            TopsMessage::AuctionInformation(ref m) => Some(m.timestamp),
+            TopsMessage::Unknown { .. } => None,
+        }
+    }
+}
+
+impl<'a> Segment<'a> {
+    /// Decodes the messages of a TOPS segment.
+    pub fn tops(&self) -> impl Iterator<Item = Result<TopsMessage<'a>>> {
+        self.messages()
+            .map(|message| message.and_then(TopsMessage::parse))
+    }
+}
+
+/// Converts a binary timestamp to the milliseconds the JSON types use.
+pub(crate) fn millis(nanos: u64) -> u64 {
+    nanos / 1_000_000
+}
@@ -1,3 +1,43 @@
+use chrono::{DateTime, Utc};
+use de;
+use serde_json::{Map, Value};
+
+// Every response type ends with an `extra` map that collects fields IEX sends
+// but this crate doesn't model yet. It is not serialized, so serializing a
+// value only produces modelled fields; see `schema_report`.
+
+/// `Money` is the numeric type used for prices and monetary amounts.
+///
+/// It is an `f64` by default. Enabling the `decimal` feature switches it to an
+/// exact `rust_decimal::Decimal`, which avoids rounding errors in arithmetic.
+#[cfg(not(feature = "decimal"))]
+pub type Money = f64;
+#[cfg(feature = "decimal")]
+pub type Money = ::rust_decimal::Decimal;
+
+string_enum! {
+    /// The source of `Quote.latest_price`.
+    pub enum CalculationPrice {
+        Tops => "tops",
+        Sip => "sip",
+        PreviousClose => "previousclose",
+        Close => "close",
+        /// Used for crypto quotes, which trade around the clock.
+        RealTime => "realtime",
+    }
+}
+
+string_enum! {
+    /// Human readable description of `Quote.latest_price`'s source.
+    pub enum LatestSource {
+        IexRealTimePrice => "IEX real time price",
+        FifteenMinuteDelayedPrice => "15 minute delayed price",
+        Close => "Close",
+        PreviousClose => "Previous close",
+        RealTimePrice => "Real time price",
+    }
+}
+
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Quote {
@@ -5,76 +45,218 @@ pub struct Quote {
     pub company_name: String,
     pub primary_exchange: String,
     pub sector: String,
-    pub calculation_price: String,
-    pub open: f64,
-    pub open_time: f64,
-    pub close: f64,
-    pub close_time: f64,
-    pub high: Option<f64>,
-    pub low: Option<f64>,
-    pub latest_price: f64,
-    pub latest_source: String,
+    pub calculation_price: CalculationPrice,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub open: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub open_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub close: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub close_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub low: Option<Money>,
+    pub latest_price: Money,
+    pub latest_source: LatestSource,
     pub latest_time: String,
-    pub latest_update: f64,
-    pub latest_volume: f64,
-    pub iex_realtime_price: f64,
-    pub iex_realtime_size: f64,
-    pub iex_last_updated: f64,
-    pub delayed_price: f64,
-    pub delayed_price_time: f64,
-    pub previous_close: f64,
-    pub change: f64,
-    pub change_percent: f64,
-    pub iex_market_percent: f64,
-    pub iex_volume: f64,
-    pub avg_total_volume: f64,
-    pub iex_bid_price: f64,
-    pub iex_bid_size: f64,
-    pub iex_ask_price: f64,
-    pub iex_ask_size: f64,
-    pub market_cap: f64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub latest_update: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub latest_volume: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_realtime_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_realtime_size: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_last_updated: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub delayed_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub delayed_price_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub previous_close: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub change: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_market_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_volume: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub avg_total_volume: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_bid_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_bid_size: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_ask_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_ask_size: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub market_cap: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
     pub pe_ratio: Option<f64>,
-    pub week52_high: f64,
-    pub week52_low: f64,
-    pub ytd_change: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52_high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52_low: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ytd_change: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
+/// A quote for a crypto pair, from `crypto` for the `market` symbol. It has
+/// the same fields as `Quote`, but equity-only fields are usually null and
+/// volumes and sizes are fractional.
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
+pub struct CryptoQuote {
+    pub symbol: String,
+    pub company_name: String,
+    #[serde(default)]
+    pub primary_exchange: Option<String>,
+    #[serde(default)]
+    pub sector: Option<String>,
+    pub calculation_price: CalculationPrice,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub open: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub open_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub close: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub close_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub low: Option<Money>,
+    pub latest_price: Money,
+    pub latest_source: LatestSource,
+    pub latest_time: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub latest_update: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub latest_volume: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_realtime_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_realtime_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_last_updated: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub delayed_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub delayed_price_time: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub previous_close: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub change: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_market_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_volume: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub avg_total_volume: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_bid_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_bid_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_ask_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub iex_ask_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub market_cap: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_ratio: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52_high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52_low: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ytd_change: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub bid_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub bid_size: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ask_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ask_size: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Bid {
-    pub price: f64,
-    pub size: f64,
-    pub timestamp: f64,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Ask {
-    pub price: f64,
-    pub size: f64,
-    pub timestamp: f64,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Trade {
-    pub price: f64,
-    pub size: f64,
-    pub trade_id: f64,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub trade_id: u64,
     #[serde(rename = "isISO")]
     pub is_iso: bool,
     pub is_odd_lot: bool,
     pub is_outside_regular_hours: bool,
     pub is_single_price_cross: bool,
     pub is_trade_through_exempt: bool,
-    pub timestamp: f64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum! {
+    /// The market session event reported by `SystemEvent`.
+    pub enum SystemEventType {
+        StartOfMessages => "O",
+        StartOfSystemHours => "S",
+        StartOfRegularMarketHours => "R",
+        EndOfRegularMarketHours => "M",
+        EndOfSystemHours => "E",
+        EndOfMessages => "C",
+    }
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemEvent {
-    pub system_event: String,
-    pub timestamp: f64,
+    pub system_event: SystemEventType,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -85,6 +267,193 @@ pub struct Book {
     pub asks: Vec<Ask>,
     pub trades: Vec<Trade>,
     pub system_event: SystemEvent,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `book` message: the symbol's whole book, best first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepBook {
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+    /// When the book last changed, in milliseconds since the epoch. IEX
+    /// leaves it out; HIST replays set it so they can be paced.
+    #[serde(
+        default,
+        deserialize_with = "de::option_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `tradingStatus` message.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepTradingStatus {
+    /// `H` halted, `O` halted but accepting orders, `P` paused or `T`
+    /// trading.
+    pub status: String,
+    /// The halt or pause reason code, e.g. `T1`; empty when trading.
+    #[serde(default)]
+    pub reason: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `opHaltStatus` message.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepOpHaltStatus {
+    pub is_halted: bool,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `ssr` message: whether the Reg SHO short sale price
+/// test restricts the symbol.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepSsr {
+    #[serde(rename = "isSSR")]
+    pub is_ssr: bool,
+    /// Why the status was set: `A`, `C`, `D`, `N` or a space.
+    pub detail: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum! {
+    /// The event reported by a DEEP `securityEvent` message.
+    pub enum SecurityEventKind {
+        MarketOpen => "MarketOpen",
+        MarketClose => "MarketClose",
+    }
+}
+
+/// The data of a DEEP `securityEvent` message.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepSecurityEvent {
+    pub security_event: SecurityEventKind,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `officialPrice` message.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepOfficialPrice {
+    /// `Open` or `Close`.
+    pub price_type: String,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub timestamp: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The data of a DEEP `auction` message.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepAuction {
+    /// `Open`, `Close`, `IPO`, `Halt` or `Volatility`.
+    pub auction_type: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub paired_shares: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub imbalance_shares: u64,
+    /// `Buy`, `Sell` or `No Imbalance`.
+    #[serde(default)]
+    pub imbalance_side: String,
+    pub reference_price: Money,
+    pub indicative_price: Money,
+    pub auction_book_price: Money,
+    pub collar_reference_price: Money,
+    pub lower_collar_price: Money,
+    pub upper_collar_price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub extension_number: u64,
+    /// The scheduled time of the auction, e.g. `09:30:00` Eastern.
+    pub start_time: String,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub last_update: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// IEX's top of book for a symbol: best bid and offer and the last sale.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tops {
+    pub symbol: String,
+    #[serde(default)]
+    pub sector: Option<String>,
+    #[serde(default)]
+    pub security_type: Option<String>,
+    pub bid_price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub bid_size: u64,
+    pub ask_price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub ask_size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub last_updated: u64,
+    pub last_sale_price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub last_sale_size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub last_sale_time: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub volume: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub market_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub seq: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The last trade of a symbol on IEX.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LastSale {
+    pub symbol: String,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub time: u64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub seq: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum! {
+    /// The kind of security, used by `Company.issue_type` and `Symbol.symbol_type`.
+    pub enum IssueType {
+        AmericanDepositoryReceipt => "ad",
+        RealEstateInvestmentTrust => "re",
+        ClosedEndFund => "ce",
+        SecondaryIssue => "si",
+        LimitedPartnership => "lp",
+        CommonStock => "cs",
+        ExchangeTradedFund => "et",
+        PreferredStock => "ps",
+        Warrant => "wt",
+        Right => "rt",
+        Unit => "ut",
+        Temporary => "temp",
+        Crypto => "crypto",
+    }
 }
 
 #[serde(rename_all = "camelCase")]
@@ -98,21 +467,82 @@ pub struct Company {
     pub description: String,
     #[serde(rename = "CEO")]
     pub ceo: String,
-    pub issue_type: String,
+    pub issue_type: IssueType,
     pub sector: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DelayedQuote {
     pub symbol: String,
-    pub delayed_price: f64,
-    // For some reason, IEX returns either a string or a float for these two.
-    // pub high: String,
-    // pub low: String,
-    pub delayed_size: f64,
-    pub delayed_price_time: f64,
-    pub processed_time: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub delayed_price: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub low: Option<Money>,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub delayed_size: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub delayed_price_time: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub processed_time: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum! {
+    /// The dividend flag reported in `Dividend.flag`.
+    pub enum DividendFlag {
+        /// Final dividend, div ends or instrument ends.
+        Final => "FI",
+        /// Liquidation, instrument liquidates.
+        Liquidation => "LI",
+        /// Proceeds of a sale of rights or shares.
+        Proceeds => "PR",
+        /// Redemption of rights.
+        Redemption => "RE",
+        /// Accrued dividend.
+        Accrued => "AC",
+        /// Payment in arrears.
+        Arrears => "AR",
+        /// Additional payment.
+        Additional => "AD",
+        /// Extra payment.
+        Extra => "EX",
+        /// Special dividend.
+        Special => "SP",
+        /// Year end.
+        YearEnd => "YE",
+        /// Unknown rate.
+        UnknownRate => "UR",
+        /// Regular dividend is suspended.
+        Suspended => "SU",
+    }
+}
+
+string_enum! {
+    /// The kind of distribution reported in `Dividend.dtype`.
+    pub enum DividendType {
+        DividendIncome => "Dividend income",
+        InterestIncome => "Interest income",
+        StockDividend => "Stock dividend",
+        ShortTermCapitalGain => "Short term capital gain",
+        MediumTermCapitalGain => "Medium term capital gain",
+        LongTermCapitalGain => "Long term capital gain",
+        UnspecifiedTermCapitalGain => "Unspecified term capital gain",
+    }
+}
+
+string_enum! {
+    /// The tax qualification reported in `Dividend.qualified`.
+    pub enum DividendQualified {
+        PartiallyQualified => "P",
+        Qualified => "Q",
+        Unqualified => "N",
+    }
 }
 
 #[serde(rename_all = "camelCase")]
@@ -122,31 +552,52 @@ pub struct Dividend {
     pub payment_date: String,
     pub record_date: String,
     pub declared_date: String,
-    pub amount: f64,
-    pub flag: String,
-    #[serde(rename = "type")]
-    pub dtype: String, // because "type" is a keyword
-    pub qualified: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub amount: Option<f64>,
+    #[serde(default)]
+    pub flag: Option<DividendFlag>,
+    #[serde(default, rename = "type")]
+    pub dtype: Option<DividendType>, // because "type" is a keyword
+    #[serde(default)]
+    pub qualified: Option<DividendQualified>,
     pub indicated: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+string_enum! {
+    /// When an earnings report is announced relative to market hours.
+    pub enum AnnounceTime {
+        BeforeOpen => "BTO",
+        DuringTrading => "DMT",
+        AfterClose => "AMC",
+    }
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Earning {
     #[serde(rename = "actualEPS")]
-    pub actual_eps: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub actual_eps: Option<f64>,
     #[serde(rename = "consensusEPS")]
-    pub consensus_eps: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub consensus_eps: Option<f64>,
     #[serde(rename = "estimatedEPS")]
-    pub estimated_eps: f64,
-    pub announce_time: String,
-    pub number_of_estimates: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub estimated_eps: Option<f64>,
+    pub announce_time: AnnounceTime,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub number_of_estimates: Option<u64>,
     #[serde(rename = "EPSSurpriseDollar")]
-    pub eps_surprise_dollar: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub eps_surprise_dollar: Option<f64>,
     #[serde(rename = "EPSReportDate")]
     pub eps_report_date: String,
     pub fiscal_period: String,
     pub fiscal_end_date: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -154,48 +605,261 @@ pub struct Earning {
 pub struct Earnings {
     pub symbol: String,
     pub earnings: Vec<Earning>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EffectiveSpread {
-    pub volume: f64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub volume: u64,
     pub venue: String,
     pub venue_name: String,
-    pub effective_spread: f64,
-    pub effective_quoted: f64,
-    pub price_improvement: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub effective_spread: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub effective_quoted: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_improvement: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Financial {
     pub report_date: String,
-    pub gross_profit: f64,
-    pub cost_of_revenue: f64,
-    pub operating_revenue: f64,
-    pub total_revenue: f64,
-    pub operating_income: f64,
-    pub net_income: f64,
-    pub research_and_development: f64,
-    pub operating_expense: f64,
-    pub current_assets: f64,
-    pub total_assets: f64,
-    pub total_liabilities: f64,
-    pub current_cash: f64,
-    pub current_debt: f64,
-    pub total_cash: f64,
-    pub total_debt: f64,
-    pub shareholder_equity: f64,
-    pub cash_change: f64,
-    pub cash_flow: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub gross_profit: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cost_of_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub operating_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub operating_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub research_and_development: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub operating_expense: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_cash: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_debt: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_cash: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_debt: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shareholder_equity: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash_change: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash_flow: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
+/// Response of the `financials` endpoint, which combines figures from the
+/// income statement, balance sheet and cash flow statement.
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Financials {
     pub symbol: String,
     pub financials: Vec<Financial>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// A single income statement, from the `income` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncomeStatement {
+    pub report_date: String,
+    #[serde(default)]
+    pub fiscal_date: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cost_of_revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub gross_profit: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub research_and_development: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub selling_general_and_admin: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub operating_expense: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub operating_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_income_expense_net: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ebit: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub interest_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pretax_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub income_tax: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub minority_interest: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_income_basic: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `income` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncomeStatements {
+    pub symbol: String,
+    pub income: Vec<IncomeStatement>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// A single balance sheet, from the `balance-sheet` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceSheet {
+    pub report_date: String,
+    #[serde(default)]
+    pub fiscal_date: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_cash: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub short_term_investments: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub receivables: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub inventory: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_current_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub long_term_investments: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub property_plant_equipment: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub goodwill: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub intangible_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub accounts_payable: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub current_long_term_debt: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_current_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_current_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub long_term_debt: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub minority_interest: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_liabilities: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub common_stock: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub retained_earnings: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub treasury_stock: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub capital_surplus: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shareholder_equity: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_tangible_assets: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `balance-sheet` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceSheets {
+    pub symbol: String,
+    pub balancesheet: Vec<BalanceSheet>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// A single cash flow statement, from the `cash-flow` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CashFlow {
+    pub report_date: String,
+    #[serde(default)]
+    pub fiscal_date: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_income: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub depreciation: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub changes_in_receivables: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub changes_in_inventories: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash_change: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash_flow: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub capital_expenditures: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub investments: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub investing_activity_other: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_investing_cash_flows: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub dividends_paid: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub net_borrowings: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub other_financing_cash_flows: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash_flow_financing: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub exchange_rate_effect: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `cash-flow` endpoint.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CashFlows {
+    pub symbol: String,
+    pub cashflow: Vec<CashFlow>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "PascalCase")]
@@ -209,6 +873,8 @@ pub struct IEXRegulationSHOThresholdSecurity {
     #[serde(rename = "SymbolinCMSSymbology")]
     pub symbolin_cms_symbology: String,
     pub security_name: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "PascalCase")]
@@ -231,92 +897,289 @@ pub struct IEXShortInterest {
     pub stock_adjustment_flag: String,
     pub new_issue_flag: String,
     pub revision_flag: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SectorPerformance {
+    #[serde(rename = "type")]
+    pub sector_type: String,
+    pub name: String,
+    pub performance: f64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub last_updated: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `upcoming-ipos` and `today-ipos` endpoints.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpoCalendar {
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub raw_data: Vec<Ipo>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub view_data: Vec<IpoSummary>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Ipo {
+    pub symbol: String,
+    pub company_name: String,
+    #[serde(default)]
+    pub expected_date: Option<String>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub lead_underwriters: Vec<String>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub underwriters: Vec<String>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub company_counsel: Vec<String>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub underwriter_counsel: Vec<String>,
+    #[serde(default)]
+    pub auditor: Option<String>,
+    #[serde(default)]
+    pub market: Option<String>,
+    #[serde(default)]
+    pub cik: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub zip: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub ceo: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub employees: Option<u64>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shares_offered: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_low: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub offer_amount: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub total_expenses: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shares_over_alloted: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shareholder_shares: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shares_outstanding: Option<u64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// The formatted summary IEX shows for an IPO; every value is display text,
+/// e.g. `"$12.00 - 15.00"`.
+#[serde(rename_all = "PascalCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IpoSummary {
+    pub company: String,
+    pub symbol: String,
+    pub price: String,
+    pub shares: String,
+    pub amount: String,
+    pub float: String,
+    pub percent: String,
+    pub market: String,
+    pub expected: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// Response of the `today-earnings` endpoint, split into announcements
+/// before the open and after the close.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TodayEarnings {
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub bto: Vec<TodayEarning>,
+    #[serde(default, deserialize_with = "de::default_on_null")]
+    pub amc: Vec<TodayEarning>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// An earnings announcement with the company's current quote. Fields this
+/// crate doesn't model end up in `earning.extra`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TodayEarning {
+    pub symbol: String,
+    pub quote: Quote,
+    #[serde(default)]
+    pub headline: Option<String>,
+    #[serde(flatten)]
+    pub earning: Earning,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Stats {
     pub company_name: String,
-    pub marketcap: f64,
-    pub beta: f64,
-    pub week52high: f64,
-    pub week52low: f64,
-    pub week52change: f64,
-    pub short_interest: f64,
-    pub short_date: String,
-    pub dividend_rate: f64,
-    pub dividend_yield: f64,
-    pub ex_dividend_date: String,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub marketcap: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub beta: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52high: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52low: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub week52change: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub short_interest: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub short_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub dividend_rate: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub dividend_yield: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub ex_dividend_date: Option<String>,
     #[serde(rename = "latestEPS")]
-    pub latest_eps: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub latest_eps: Option<f64>,
     #[serde(rename = "latestEPSDate")]
-    pub latest_eps_date: String,
-    pub shares_outstanding: f64,
-    pub float: f64,
-    pub return_on_equity: f64,
+    #[serde(default, deserialize_with = "de::option_string")]
+    pub latest_eps_date: Option<String>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub shares_outstanding: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub float: Option<u64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub return_on_equity: Option<f64>,
     #[serde(rename = "consensusEPS")]
-    pub consensus_eps: f64,
-    pub number_of_estimates: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub consensus_eps: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub number_of_estimates: Option<u64>,
     #[serde(rename = "EPSSurprisePercent")]
-    pub eps_surprise_percent: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub eps_surprise_percent: Option<f64>,
     #[serde(rename = "EPSSurpriseDollar")]
+    #[serde(default, deserialize_with = "de::option_number")]
     pub eps_surprise_dollar: Option<f64>,
     pub symbol: String,
     #[serde(rename = "EBITDA")]
-    pub ebitda: f64,
-    pub revenue: f64,
-    pub gross_profit: f64,
-    pub cash: f64,
-    pub debt: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ebitda: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub gross_profit: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub cash: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub debt: Option<f64>,
     #[serde(rename = "ttmEPS")]
-    pub ttm_eps: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ttm_eps: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
     pub return_on_capital: Option<f64>,
-    pub revenue_per_share: f64,
-    pub revenue_per_employee: f64,
-    pub pe_ratio_high: f64,
-    pub pe_ratio_low: f64,
-    pub return_on_assets: f64,
-    pub profit_margin: f64,
-    pub price_to_sales: f64,
-    pub price_to_book: f64,
-    pub day200_moving_avg: f64,
-    pub day50_moving_avg: f64,
-    pub institution_percent: f64,
-    pub insider_percent: f64,
-    pub short_ratio: f64,
-    pub year5_change_percent: f64,
-    pub year2_change_percent: f64,
-    pub year1_change_percent: f64,
-    pub ytd_change_percent: f64,
-    pub month6_change_percent: f64,
-    pub month3_change_percent: f64,
-    pub month1_change_percent: f64,
-    pub day5_change_percent: f64,
-    pub day30_change_percent: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue_per_share: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub revenue_per_employee: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_ratio_high: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub pe_ratio_low: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub return_on_assets: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub profit_margin: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_to_sales: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub price_to_book: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day200_moving_avg: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day50_moving_avg: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub institution_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub insider_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub short_ratio: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year5_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year2_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub year1_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub ytd_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month6_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month3_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub month1_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day5_change_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub day30_change_percent: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Logo {
     pub url: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct News {
-    pub datetime: String,
+    #[serde(deserialize_with = "de::datetime")]
+    pub datetime: DateTime<Utc>,
     pub headline: String,
     pub source: String,
     pub url: String,
     pub summary: String,
-    pub related: String,
+    /// Ticker symbols the article relates to. IEX sends these comma-separated;
+    /// they serialize as a list.
+    #[serde(deserialize_with = "de::comma_separated")]
+    pub related: Vec<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub has_paywall: Option<bool>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Price {
-    pub price: f64,
-    pub time: f64,
+    pub price: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub time: u64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -324,8 +1187,12 @@ pub struct Price {
 pub struct OHLC {
     pub open: Price,
     pub close: Price,
-    pub high: Option<f64>,
-    pub low: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub high: Option<Money>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub low: Option<Money>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -333,15 +1200,19 @@ pub struct OHLC {
 pub struct Previous {
     pub symbol: String,
     pub date: String,
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub volume: f64,
-    pub unadjusted_volume: f64,
-    pub change: f64,
+    pub open: Money,
+    pub high: Money,
+    pub low: Money,
+    pub close: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub volume: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub unadjusted_volume: u64,
+    pub change: Money,
     pub change_percent: f64,
-    pub vwap: f64,
+    pub vwap: Money,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -349,6 +1220,8 @@ pub struct Previous {
 pub struct Relevant {
     pub peers: bool,
     pub symbols: Vec<String>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -361,33 +1234,44 @@ pub struct Split {
     pub ratio: f64,
     pub to_factor: f64,
     pub for_factor: f64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ChartDataPoint {
     pub date: String,
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub volume: f64,
-    pub unadjusted_volume: f64,
-    pub change: f64,
+    pub open: Money,
+    pub high: Money,
+    pub low: Money,
+    pub close: Money,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub volume: u64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub unadjusted_volume: u64,
+    pub change: Money,
     pub change_percent: f64,
-    pub vwap: f64,
+    pub vwap: Money,
     pub label: String,
     pub change_over_time: f64,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VolumeByVenue {
-    pub volume: f64,
+    #[serde(deserialize_with = "de::lenient_u64")]
+    pub volume: u64,
     pub venue: String,
     pub venue_name: String,
-    pub market_percent: f64,
-    pub avg_market_percent: f64,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub market_percent: Option<f64>,
+    #[serde(default, deserialize_with = "de::option_number")]
+    pub avg_market_percent: Option<f64>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
 
 #[serde(rename_all = "camelCase")]
@@ -398,6 +1282,8 @@ pub struct Symbol {
     pub date: String,
     pub is_enabled: bool,
     #[serde(rename = "type")]
-    pub symbol_type: String,
+    pub symbol_type: IssueType,
     pub iex_id: String,
+    #[serde(flatten, skip_serializing)]
+    pub extra: Map<String, Value>,
 }
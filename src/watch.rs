@@ -0,0 +1,178 @@
+//! Quote events from polling, for plans without streaming.
+use std::collections::{HashMap, VecDeque};
+use std::thread;
+use std::time;
+
+use stream::Event;
+use types::Quote;
+use {Client, Result};
+
+/// `Watch` is an endless iterator over quote events, like a `Quotes`
+/// stream, fetched by polling about every `interval`. A quote is returned
+/// only when its `latest_update` is newer than the last one returned for its
+/// symbol, so unchanged and stale quotes are skipped. Each poll's quotes are
+/// returned oldest first.
+pub struct Watch<'a> {
+    poll: Box<dyn FnMut() -> Result<HashMap<String, Quote>> + 'a>,
+    interval: time::Duration,
+    jitter: f64,
+    seed: u64,
+    latest: HashMap<String, u64>,
+    pending: VecDeque<Event>,
+    polled: bool,
+}
+
+impl<'a> Watch<'a> {
+    /// Creates a watch from any function returning the latest quotes by
+    /// symbol.
+    pub fn new<F>(interval: time::Duration, poll: F) -> Watch<'a>
+    where
+        F: FnMut() -> Result<HashMap<String, Quote>> + 'a,
+    {
+        let seed = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|now| now.subsec_nanos())
+            .unwrap_or_default();
+        Watch {
+            poll: Box::new(poll),
+            interval,
+            jitter: 0.1,
+            seed: u64::from(seed) | 1,
+            latest: HashMap::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        }
+    }
+
+    /// Varies each wait by up to `jitter` times the interval either way, so
+    /// many watches don't poll in step. Defaults to 0.1; NaN means none.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = if jitter.is_nan() {
+            0.0
+        } else {
+            jitter.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Returns the next wait: the interval with jitter applied.
+    fn wait(&mut self) -> time::Duration {
+        // xorshift64: plenty for spreading out polls.
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let unit = (self.seed >> 11) as f64 / (1u64 << 53) as f64;
+        self.interval.mul_f64(1.0 + self.jitter * (2.0 * unit - 1.0))
+    }
+}
+
+impl<'a> Iterator for Watch<'a> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.polled {
+                let wait = self.wait();
+                thread::sleep(wait);
+            }
+            self.polled = true;
+
+            let quotes = match (self.poll)() {
+                Ok(quotes) => quotes,
+                Err(e) => return Some(Err(e)),
+            };
+            let mut changed: Vec<Quote> = quotes
+                .into_values()
+                .filter(|quote| {
+                    self.latest
+                        .get(&quote.symbol)
+                        .is_none_or(|&latest| quote.latest_update > latest)
+                })
+                .collect();
+            changed.sort_by(|a, b| {
+                (a.latest_update, &a.symbol).cmp(&(b.latest_update, &b.symbol))
+            });
+            for quote in changed {
+                self.latest.insert(quote.symbol.clone(), quote.latest_update);
+                self.pending.push_back(Event::Quote(Box::new(quote)));
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Polls quotes for `symbols` about every `interval`, returning each
+    /// changed quote as an event.
+    pub fn watch<'a, S>(&'a self, symbols: &[S], interval: time::Duration) -> Watch<'a>
+    where
+        S: AsRef<str>,
+    {
+        let symbols: Vec<String> = symbols.iter().map(|s| s.as_ref().to_string()).collect();
+        Watch::new(interval, move || self.quotes(&symbols))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{self, Value};
+
+    fn quotes(updates: &[(&str, u64)]) -> HashMap<String, Quote> {
+        updates
+            .iter()
+            .map(|&(symbol, latest_update)| {
+                let mut quote: Value =
+                    serde_json::from_str(include_str!("../tests/fixtures/quote.json")).unwrap();
+                quote["symbol"] = json!(symbol);
+                quote["latestUpdate"] = json!(latest_update);
+                (symbol.to_string(), serde_json::from_value(quote).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn watch_skips_unchanged_and_stale_quotes() {
+        let mut polls = vec![
+            quotes(&[("SNAP", 2), ("AAPL", 1)]),
+            quotes(&[("SNAP", 2), ("AAPL", 1)]),
+            quotes(&[("SNAP", 1), ("AAPL", 3)]),
+            quotes(&[("SNAP", 4), ("AAPL", 3)]),
+        ].into_iter();
+        let watch = Watch::new(time::Duration::from_millis(0), move || {
+            Ok(polls.next().unwrap_or_default())
+        });
+
+        let updates: Vec<(String, u64)> = watch
+            .take(4)
+            .map(|event| match event.unwrap() {
+                Event::Quote(quote) => (quote.symbol, quote.latest_update),
+                event => panic!("expected a quote, got {:?}", event),
+            })
+            .collect();
+        assert_eq!(
+            updates,
+            vec![
+                ("AAPL".to_string(), 1),
+                ("SNAP".to_string(), 2),
+                ("AAPL".to_string(), 3),
+                ("SNAP".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn watch_waits_within_jitter() {
+        let interval = time::Duration::from_millis(1000);
+        let mut watch = Watch::new(interval, || Ok(HashMap::new())).with_jitter(0.2);
+        for _ in 0..100 {
+            let wait = watch.wait();
+            assert!(wait >= interval.mul_f64(0.8) && wait <= interval.mul_f64(1.2));
+        }
+
+        let mut watch = Watch::new(interval, || Ok(HashMap::new())).with_jitter(f64::NAN);
+        assert_eq!(watch.wait(), interval);
+    }
+}
@@ -0,0 +1,336 @@
+//! Writes the synthetic captures in `tests/fixtures/pcap`.
+//!
+//! Every field is spelled out here, so the values the tests expect can be
+//! checked against the bytes. Run the `pcap` tests with
+//! `IEX_REGENERATE_CAPTURES=1` to rewrite the files after changing them.
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+/// Timestamp of the first message, 2017-10-31 14:10 UTC, in nanoseconds.
+pub const T: u64 = 1_509_459_000_000_000_000;
+
+/// A millisecond in nanoseconds. DEEP messages are this far apart, so
+/// replays can be filtered and paced by their times.
+pub const MS: u64 = 1_000_000;
+
+/// `TOPS_PROTOCOL` and `DEEP_PROTOCOL` on the wire.
+const TOPS: u16 = 0x8003;
+const DEEP: u16 = 0x8004;
+
+/// A message or header under construction, little-endian unless noted.
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    fn new() -> Bytes {
+        Bytes(Vec::new())
+    }
+
+    fn raw(mut self, bytes: &[u8]) -> Bytes {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn u8(self, value: u8) -> Bytes {
+        self.raw(&[value])
+    }
+
+    fn u16(self, value: u16) -> Bytes {
+        self.raw(&value.to_le_bytes())
+    }
+
+    fn u16_be(self, value: u16) -> Bytes {
+        self.raw(&value.to_be_bytes())
+    }
+
+    fn u32(self, value: u32) -> Bytes {
+        self.raw(&value.to_le_bytes())
+    }
+
+    fn u64(self, value: u64) -> Bytes {
+        self.raw(&value.to_le_bytes())
+    }
+
+    /// A price, in units of 1/10000 of a dollar.
+    fn price(self, value: i64) -> Bytes {
+        self.raw(&value.to_le_bytes())
+    }
+
+    /// A symbol, padded with spaces to 8 bytes.
+    fn symbol(self, symbol: &str) -> Bytes {
+        self.raw(format!("{:<8}", symbol).as_bytes())
+    }
+
+    fn header(self, kind: u8, flags: u8, timestamp: u64) -> Bytes {
+        self.u8(kind).u8(flags).u64(timestamp)
+    }
+}
+
+fn message(kind: u8, flags: u8, timestamp: u64) -> Bytes {
+    Bytes::new().header(kind, flags, timestamp)
+}
+
+/// A DEEP price level update for SPY.
+fn level(side: u8, flags: u8, timestamp: u64, size: u32, price: i64) -> Vec<u8> {
+    message(side, flags, timestamp)
+        .symbol("SPY")
+        .u32(size)
+        .price(price)
+        .0
+}
+
+/// An IEX-TP segment of `messages`, the first numbered `seq`, starting
+/// `offset` bytes into the stream.
+fn segment(protocol: u16, session: u32, seq: u64, offset: u64, messages: &[Vec<u8>]) -> Vec<u8> {
+    let payload = messages
+        .iter()
+        .fold(Bytes::new(), |bytes, m| bytes.u16(m.len() as u16).raw(m));
+    Bytes::new()
+        .u8(1)
+        .u8(0)
+        .u16(protocol)
+        .u32(1)
+        .u32(session)
+        .u16(payload.0.len() as u16)
+        .u16(messages.len() as u16)
+        .u64(offset)
+        .u64(seq)
+        .u64(T)
+        .raw(&payload.0)
+        .0
+}
+
+/// Wraps `payload` in IPv4 and UDP headers, or just IPv4 if `protocol`
+/// isn't UDP, from 10.0.0.1 to the multicast group 233.215.21.4.
+fn ip(payload: &[u8], protocol: u8) -> Vec<u8> {
+    let udp = Bytes::new()
+        .u16_be(10378)
+        .u16_be(10378)
+        .u16_be(8 + payload.len() as u16)
+        .u16_be(0)
+        .raw(payload);
+    Bytes::new()
+        .u8(0x45)
+        .u8(0)
+        .u16_be(20 + udp.0.len() as u16)
+        .u16_be(0)
+        .u16_be(0x4000)
+        .u8(64)
+        .u8(protocol)
+        .u16_be(0)
+        .raw(&[10, 0, 0, 1])
+        .raw(&[233, 215, 21, 4])
+        .raw(&udp.0)
+        .0
+}
+
+/// Wraps an IPv4 packet in an Ethernet frame, optionally VLAN-tagged.
+fn ethernet(ip: &[u8], vlan: bool) -> Vec<u8> {
+    let frame = Bytes::new()
+        .raw(&[0x01, 0x00, 0x5e, 0x57, 0x15, 0x04])
+        .raw(&[0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    let frame = if vlan {
+        frame.raw(&[0x81, 0x00, 0x00, 0x0a])
+    } else {
+        frame
+    };
+    frame.raw(&[0x08, 0x00]).raw(ip).0
+}
+
+/// A classic pcap file of `frames`, all captured in the second of `T`.
+fn pcap(frames: &[Vec<u8>]) -> Vec<u8> {
+    let header = Bytes::new()
+        .u32(0xa1b2_c3d4)
+        .u16(2)
+        .u16(4)
+        .u32(0)
+        .u32(0)
+        .u32(65535)
+        .u32(1);
+    frames
+        .iter()
+        .enumerate()
+        .fold(header, |bytes, (i, frame)| {
+            bytes
+                .u32((T / 1_000_000_000) as u32)
+                .u32(i as u32)
+                .u32(frame.len() as u32)
+                .u32(frame.len() as u32)
+                .raw(frame)
+        })
+        .0
+}
+
+/// The TOPS frames: two segments and a heartbeat, with a TCP packet after the
+/// first segment.
+fn tops_frames() -> Vec<Vec<u8>> {
+    let first = vec![
+        message(b'S', b'R', T).0,
+        message(b'D', 0x20, T + 1)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_605_700)
+            .u8(1)
+            .0,
+        message(b'H', b'H', T + 2).symbol("ZIEXT").raw(b"T1  ").0,
+        message(b'O', b'O', T + 3).symbol("ZIEXT").0,
+        message(b'P', 1, T + 4).symbol("ZIEXT").raw(b"A").0,
+        message(b'Q', 0, T + 5)
+            .symbol("SPY")
+            .u32(9700)
+            .price(2_609_500)
+            .price(2_609_600)
+            .u32(1000)
+            .0,
+    ];
+    let second = vec![
+        message(b'T', 0x80, T + 6)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_609_550)
+            .u64(429_974)
+            .0,
+        message(b'X', b'Q', T + 7).symbol("SPY").price(2_609_000).0,
+        message(b'B', 0, T + 8)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_609_550)
+            .u64(429_974)
+            .0,
+        message(b'A', b'C', T + 9)
+            .symbol("ZIEXT")
+            .u32(50_000)
+            .price(1_000_000)
+            .price(1_000_500)
+            .u32(1000)
+            .raw(b"B")
+            .u8(0)
+            .u32(1_509_480_000)
+            .price(1_000_600)
+            .price(1_000_000)
+            .price(900_000)
+            .price(1_100_000)
+            .0,
+        // An unknown message type, which is skipped.
+        b"zabc".to_vec(),
+    ];
+    let first = segment(TOPS, 42, 1, 0, &first);
+    let second = segment(TOPS, 42, 7, first.len() as u64 - 40, &second);
+    let heartbeat = segment(TOPS, 42, 12, (first.len() + second.len()) as u64 - 80, &[]);
+    vec![
+        ethernet(&ip(&first, 17), false),
+        ethernet(&ip(b"not iex", 6), false),
+        ethernet(&ip(&second, 17), false),
+        ethernet(&ip(&heartbeat, 17), false),
+    ]
+}
+
+/// `tops.pcap`.
+pub fn tops_pcap() -> Vec<u8> {
+    pcap(&tops_frames())
+}
+
+/// `tops.pcap.gz`, which decompresses to `tops.pcap`.
+pub fn tops_pcap_gz() -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tops_pcap()).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// `tops.pcapng`: the TOPS frames with a statistics block to skip, nanosecond
+/// timestamps 1µs apart and the second segment VLAN-tagged.
+pub fn tops_pcapng() -> Vec<u8> {
+    fn block(kind: u32, body: Bytes) -> Bytes {
+        let mut body = body.0;
+        let padded = body.len().div_ceil(4) * 4;
+        body.resize(padded, 0);
+        let len = body.len() as u32 + 12;
+        Bytes::new().u32(kind).u32(len).raw(&body).u32(len)
+    }
+
+    let section = Bytes::new()
+        .u32(0x1a2b_3c4d)
+        .u16(1)
+        .u16(0)
+        .u64(u64::MAX);
+    // if_tsresol = 9, for nanoseconds, then the end of options.
+    let interface = Bytes::new()
+        .u16(1)
+        .u16(0)
+        .u32(65535)
+        .u16(9)
+        .u16(1)
+        .u8(9)
+        .raw(&[0; 3])
+        .u16(0)
+        .u16(0);
+    let bytes = Bytes::new()
+        .raw(&block(0x0a0d_0d0a, section).0)
+        .raw(&block(1, interface).0)
+        .raw(&block(5, Bytes::new().raw(b"ignored statistics")).0);
+    tops_frames()
+        .into_iter()
+        .enumerate()
+        .fold(bytes, |bytes, (i, frame)| {
+            let frame = if i == 2 {
+                ethernet(&frame[14..], true)
+            } else {
+                frame
+            };
+            let timestamp = T + 1000 * i as u64;
+            let packet = Bytes::new()
+                .u32(0)
+                .u32((timestamp >> 32) as u32)
+                .u32(timestamp as u32)
+                .u32(frame.len() as u32)
+                .u32(frame.len() as u32)
+                .raw(&frame);
+            bytes.raw(&block(6, packet).0)
+        })
+        .0
+}
+
+/// `deep.pcap`: two DEEP segments of updates to SPY's book, with messages
+/// milliseconds apart.
+pub fn deep_pcap() -> Vec<u8> {
+    let first = vec![
+        message(b'S', b'O', T).0,
+        message(b'D', 0x20, T + MS)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_605_700)
+            .u8(1)
+            .0,
+        message(b'H', b'T', T + 2 * MS).symbol("SPY").raw(b"    ").0,
+        level(b'8', 0, T + 10 * MS, 200, 2_609_400),
+        level(b'8', 1, T + 10 * MS, 100, 2_609_500),
+        level(b'5', 1, T + 11 * MS, 300, 2_609_700),
+    ];
+    let second = vec![
+        message(b'E', b'O', T + 12 * MS).symbol("SPY").0,
+        level(b'5', 0, T + 13 * MS, 0, 2_609_700),
+        level(b'5', 1, T + 13 * MS, 400, 2_609_800),
+        message(b'T', 0, T + 14 * MS)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_609_600)
+            .u64(1)
+            .0,
+        message(b'B', 0, T + 15 * MS)
+            .symbol("SPY")
+            .u32(100)
+            .price(2_609_600)
+            .u64(1)
+            .0,
+        // Starts an event that never completes.
+        level(b'8', 0, T + 16 * MS, 500, 2_609_300),
+        message(b'H', b'H', T + 17 * MS).symbol("SPY").raw(b"T1  ").0,
+    ];
+    let first = segment(DEEP, 43, 1, 0, &first);
+    let second = segment(DEEP, 43, 7, first.len() as u64 - 40, &second);
+    pcap(&[
+        ethernet(&ip(&first, 17), false),
+        ethernet(&ip(&second, 17), false),
+    ])
+}
@@ -0,0 +1,199 @@
+//! Deserializes saved IEX Cloud responses from `tests/fixtures/cloud`.
+#![cfg(feature = "iex-cloud")]
+extern crate iex;
+extern crate serde;
+extern crate serde_json;
+
+use iex::*;
+use std::num::NonZeroUsize;
+
+fn parse<T>(json: &str) -> T
+where
+    T: for<'de> serde::Deserialize<'de> + serde::Serialize,
+{
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    let report = schema_report::<T>(&value).unwrap();
+    assert!(report.unknown.is_empty(), "unknown fields: {:?}", report.unknown);
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn advanced_stats_fixture() {
+    let stats: AdvancedStats = parse(include_str!("fixtures/cloud/advanced_stats.json"));
+    assert_eq!(stats.ttm_eps, Some(12.58));
+    assert_eq!(stats.ebitda, Some("81860000000".parse().unwrap()));
+    assert_eq!(stats.next_dividend_date, None);
+    assert_eq!(stats.forward_pe_ratio, Some(22.81));
+}
+
+#[test]
+fn estimates_fixture() {
+    let estimates: Estimates = parse(include_str!("fixtures/cloud/estimates.json"));
+    assert_eq!(estimates.estimates[0].consensus_eps, Some(2.26));
+    assert_eq!(estimates.estimates[0].fiscal_period, Some("Q2 2020".to_string()));
+}
+
+#[test]
+fn price_target_fixture() {
+    let target: PriceTarget = parse(include_str!("fixtures/cloud/price_target.json"));
+    assert_eq!(target.number_of_analysts, Some(34));
+    assert_eq!(target.price_target_high, Some("400".parse().unwrap()));
+}
+
+#[test]
+fn recommendation_trends_fixture() {
+    let trends: Vec<RecommendationTrend> =
+        parse(include_str!("fixtures/cloud/recommendation_trends.json"));
+    assert_eq!(trends[0].rating_buy, Some(14));
+    assert_eq!(trends[0].corporate_actions_applied_date, None);
+}
+
+#[test]
+fn ownership_fixture() {
+    let owners: Vec<Owner> = parse(include_str!("fixtures/cloud/fund_ownership.json"));
+    assert_eq!(owners[0].adj_holding, Some(150000));
+}
+
+#[test]
+fn insider_fixtures() {
+    let transactions: Vec<InsiderTransaction> =
+        parse(include_str!("fixtures/cloud/insider_transactions.json"));
+    assert_eq!(transactions[0].tran_shares, Some(-17716));
+
+    let roster: Vec<Insider> = parse(include_str!("fixtures/cloud/insider_roster.json"));
+    assert_eq!(roster[0].entity_name, "Timothy D. Cook");
+}
+
+#[test]
+fn cloud_endpoints_take_period_and_last() {
+    let endpoint = StocksEndpoint::Estimates {
+        period: Some(Period::Annual),
+        last: NonZeroUsize::new(2),
+    };
+    assert_eq!(endpoint.to_endpoint(), "estimates?period=annual&last=2");
+    let endpoint = StocksEndpoint::BalanceSheet {
+        period: Some(Period::Quarter),
+        last: None,
+    };
+    assert_eq!(endpoint.to_endpoint(), "balance-sheet?period=quarter");
+    assert_eq!(
+        Client::cloud("pk_test").url("stock/aapl/advanced-stats"),
+        "https://cloud.iexapis.com/stable/stock/aapl/advanced-stats?token=pk_test"
+    );
+    assert_eq!(
+        Client::cloud("pk_a&b=c").url("stock/aapl/quote"),
+        "https://cloud.iexapis.com/stable/stock/aapl/quote?token=pk_a%26b%3Dc"
+    );
+}
+
+#[test]
+fn fx_fixtures() {
+    let latest: Vec<FxRate> = parse(include_str!("fixtures/cloud/fx_latest.json"));
+    let rates = FxRates::new(latest);
+    assert_eq!(rates.rate("USD", "GBP"), Some("0.82101".parse().unwrap()));
+
+    let historical: Vec<Vec<FxRate>> = parse(include_str!("fixtures/cloud/fx_historical.json"));
+    assert_eq!(historical[0][1].date, Some("2020-03-17".to_string()));
+}
+
+#[test]
+fn treasury_fixture() {
+    let observations: Vec<Observation> = parse(include_str!("fixtures/cloud/treasury.json"));
+    assert_eq!(observations[0].value, Some(0.73));
+    assert_eq!(observations[0].key, Some("DGS10".to_string()));
+    assert_eq!(observations[1].value, None);
+}
+
+#[test]
+fn data_endpoints() {
+    assert_eq!(
+        DataEndpoint::FxLatest {
+            symbols: vec!["USDCAD".into(), "USDGBP".into()],
+        }.to_endpoint(),
+        "fx/latest?symbols=USDCAD,USDGBP"
+    );
+    assert_eq!(
+        DataEndpoint::FxConvert {
+            symbols: vec!["USDCAD".into()],
+            amount: Some("83.2".into()),
+        }.to_endpoint(),
+        "fx/convert?symbols=USDCAD&amount=83.2"
+    );
+    assert_eq!(
+        DataEndpoint::FxHistorical {
+            symbols: vec!["USDCAD".into()],
+            from: Some("2020-03-16".into()),
+            to: None,
+            last: Some(5),
+        }.to_endpoint(),
+        "fx/historical?symbols=USDCAD&from=2020-03-16&last=5"
+    );
+    // Each pair is encoded; the commas between them are not.
+    assert_eq!(
+        DataEndpoint::FxLatest {
+            symbols: vec!["USD&CAD".into(), "USD GBP".into()],
+        }.to_endpoint(),
+        "fx/latest?symbols=USD%26CAD,USD%20GBP"
+    );
+    assert_eq!(
+        DataEndpoint::MarketDataPoint {
+            key: "DCOILWTICO".into(),
+        }.to_endpoint(),
+        "data-points/market/DCOILWTICO"
+    );
+    assert_eq!(
+        DataEndpoint::Treasury {
+            key: "DGS10".into(),
+            last: Some(30),
+        }.to_endpoint(),
+        "time-series/treasury/DGS10?last=30"
+    );
+}
+
+#[test]
+fn reported_financials_fixture() {
+    let series: TimeSeries<ReportedFinancials> =
+        parse(include_str!("fixtures/cloud/reported_financials.json"));
+    let filing = &series.rows[0];
+    assert_eq!(filing.subkey, "10-Q");
+    assert_eq!(filing.form_fiscal_quarter, Some(2));
+    assert_eq!(filing.fact("NetIncomeLoss"), Some(11249000000.0));
+    assert_eq!(filing.fact("dei:DocumentFiscalPeriodFocus"), None);
+}
+
+#[test]
+fn premium_dataset_fixture() {
+    let series: TimeSeries<UpcomingEarning> =
+        parse(include_str!("fixtures/cloud/upcoming_earnings.json"));
+    assert_eq!(series.rows[0].earnings_date, Some("2020-07-30".to_string()));
+    assert_eq!(series.rows[0].fiscal_quarter, Some(3));
+    assert_eq!(series.rows[0].time_of_day, Some("AMC".to_string()));
+
+    // Unknown datasets fall back to plain JSON rows.
+    let series: TimeSeries =
+        serde_json::from_str(include_str!("fixtures/cloud/upcoming_earnings.json")).unwrap();
+    assert_eq!(series.rows[0]["symbol"], "AAPL");
+}
+
+#[test]
+fn time_series_query_paths() {
+    let query = TimeSeriesQuery::dataset::<ReportedFinancials>()
+        .key("AAPL")
+        .subkey("10-K")
+        .from("2018-01-01")
+        .to("2019-12-31")
+        .build()
+        .unwrap();
+    assert_eq!(
+        DataEndpoint::from(query).to_endpoint(),
+        "time-series/REPORTED_FINANCIALS/AAPL/10-K?from=2018-01-01&to=2019-12-31"
+    );
+
+    let query = TimeSeriesQuery::builder()
+        .id("TREASURY")
+        .key("DGS 10")
+        .range("1y")
+        .build()
+        .unwrap();
+    assert_eq!(query.to_endpoint(), "time-series/TREASURY/DGS%2010?range=1y");
+}
@@ -0,0 +1,130 @@
+//! Deserializes every response in `tests/fixtures/edge`, a corpus of real
+//! edge cases (ETFs, ADRs, new listings and halted stocks) where IEX sends
+//! nulls, numeric strings and other surprises.
+//!
+//! Fixtures are named `<case>_<type>.json`; the suffix picks the type.
+extern crate iex;
+extern crate serde;
+extern crate serde_json;
+
+use iex::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/edge")
+}
+
+fn read(name: &str) -> String {
+    fs::read_to_string(fixture_dir().join(name)).unwrap()
+}
+
+fn parse<T>(name: &str) -> T
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    serde_json::from_str(&read(name)).unwrap()
+}
+
+fn check<T>(json: &str) -> std::result::Result<(), String>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    serde_json::from_str::<T>(json)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn all_edge_case_fixtures_deserialize() {
+    let mut failures = Vec::new();
+    let mut count = 0;
+
+    for entry in fs::read_dir(fixture_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let json = fs::read_to_string(&path).unwrap();
+
+        let result = if name.ends_with("_delayed_quote.json") {
+            check::<DelayedQuote>(&json)
+        } else if name.ends_with("_quote.json") {
+            check::<Quote>(&json)
+        } else if name.ends_with("_book.json") {
+            check::<Book>(&json)
+        } else if name.ends_with("_company.json") {
+            check::<Company>(&json)
+        } else if name.ends_with("_dividends.json") {
+            check::<Vec<Dividend>>(&json)
+        } else if name.ends_with("_earnings.json") {
+            check::<Earnings>(&json)
+        } else if name.ends_with("_financials.json") {
+            check::<Financials>(&json)
+        } else if name.ends_with("_stats.json") {
+            check::<Stats>(&json)
+        } else {
+            Err("no type for this fixture name".to_string())
+        };
+
+        count += 1;
+        if let Err(e) = result {
+            failures.push(format!("{}: {}", name, e));
+        }
+    }
+
+    assert!(count > 0, "no fixtures found");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn etf_quote_has_no_iex_or_pe_data() {
+    let quote: Quote = parse("spy_quote.json");
+    assert_eq!(quote.pe_ratio, None);
+    assert_eq!(quote.iex_realtime_price, None);
+    assert_eq!(quote.latest_source, LatestSource::FifteenMinuteDelayedPrice);
+}
+
+#[test]
+fn adr_quote_accepts_numeric_strings() {
+    let quote: Quote = parse("baba_quote.json");
+    assert_eq!(quote.pe_ratio, Some(47.41));
+    assert_eq!(quote.market_cap, Some("463162270000".parse().unwrap()));
+}
+
+#[test]
+fn new_listing_has_no_history() {
+    let quote: Quote = parse("ipo_quote.json");
+    assert_eq!(quote.previous_close, None);
+    assert_eq!(quote.week52_high, None);
+    assert_eq!(quote.avg_total_volume, None);
+
+    let stats: Stats = parse("ipo_stats.json");
+    assert_eq!(stats.beta, None);
+    assert_eq!(stats.shares_outstanding, Some(394387000));
+    assert_eq!(stats.short_date, None);
+
+    let earnings: Earnings = parse("ipo_earnings.json");
+    assert_eq!(earnings.earnings[0].actual_eps, None);
+}
+
+#[test]
+fn halted_quote_has_no_session_data() {
+    let quote: Quote = parse("halted_quote.json");
+    assert_eq!(quote.open, None);
+    assert_eq!(quote.latest_volume, None);
+    assert_eq!(quote.calculation_price, CalculationPrice::PreviousClose);
+}
+
+#[test]
+fn delayed_quote_prices_accept_strings_and_floats() {
+    let quote: DelayedQuote = parse("halted_delayed_quote.json");
+    assert_eq!(quote.delayed_price, Some("32.33".parse().unwrap()));
+    assert_eq!(quote.high, Some("32.6".parse().unwrap()));
+    assert_eq!(quote.low, Some("31.89".parse().unwrap()));
+
+    let quote: DelayedQuote = serde_json::from_str(
+        r#"{"symbol":"LJPC","delayedPrice":null,"delayedSize":0,
+            "delayedPriceTime":1521748740117,"processedTime":1521748742081}"#,
+    )
+    .unwrap();
+    assert_eq!((quote.delayed_price, quote.high, quote.low), (None, None, None));
+}
@@ -0,0 +1,350 @@
+//! Round-trip tests for turning endpoints into paths and back again, and
+//! for the serialized form used in config files.
+extern crate iex;
+#[macro_use]
+extern crate proptest;
+extern crate reqwest;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+
+use iex::*;
+use proptest::prelude::*;
+use reqwest::Url;
+use std::convert::TryFrom;
+use std::num::NonZeroUsize;
+
+fn duration() -> BoxedStrategy<Duration<'static>> {
+    prop_oneof![
+        Just(Duration::FiveYears),
+        Just(Duration::TwoYears),
+        Just(Duration::OneYear),
+        Just(Duration::YearToDate),
+        Just(Duration::SixMonths),
+        Just(Duration::ThreeMonths),
+        Just(Duration::OneMonth),
+        Just(Duration::OneDay),
+        prop::sample::select(vec!["20180129", "20190301", "20201231"]).prop_map(Duration::Date),
+        Just(Duration::Dynamic),
+        Just(Duration::None),
+    ].boxed()
+}
+
+fn list_param() -> BoxedStrategy<ListParam> {
+    prop_oneof![
+        Just(ListParam::MostActive),
+        Just(ListParam::Gainers),
+        Just(ListParam::Losers),
+        Just(ListParam::IexVolume),
+        Just(ListParam::IexPercent),
+    ].boxed()
+}
+
+fn chart_param() -> BoxedStrategy<ChartParam> {
+    prop_oneof![
+        any::<bool>().prop_map(ChartParam::Reset),
+        any::<bool>().prop_map(ChartParam::Simplify),
+        any::<usize>().prop_map(ChartParam::Interval),
+        any::<bool>().prop_map(ChartParam::ChangeFromClose),
+        any::<usize>().prop_map(ChartParam::Last),
+    ].boxed()
+}
+
+fn statement() -> BoxedStrategy<(Option<Period>, Option<NonZeroUsize>)> {
+    (
+        prop::option::of(prop_oneof![Just(Period::Annual), Just(Period::Quarter)]),
+        prop::option::of((1..usize::MAX).prop_map(|last| NonZeroUsize::new(last).unwrap())),
+    ).boxed()
+}
+
+fn stock_endpoint() -> BoxedStrategy<StocksEndpoint<'static>> {
+    prop_oneof![
+        Just(StocksEndpoint::Book),
+        (
+            duration(),
+            prop::option::of(prop::collection::vec(chart_param(), 0..5))
+        ).prop_map(|(duration, params)| StocksEndpoint::Chart { duration, params }),
+        (
+            prop_oneof![
+                Just(CollectionType::Sector),
+                Just(CollectionType::Tag),
+                Just(CollectionType::List),
+            ],
+            "\\PC+",
+        ).prop_map(|(collection, name)| StocksEndpoint::Collection {
+            collection,
+            name: name.into(),
+        }),
+        Just(StocksEndpoint::Company),
+        Just(StocksEndpoint::Crypto),
+        Just(StocksEndpoint::DelayedQuote),
+        duration().prop_map(|duration| StocksEndpoint::Dividends { duration }),
+        Just(StocksEndpoint::Earnings),
+        Just(StocksEndpoint::EffectiveSpread),
+        statement().prop_map(|(period, last)| StocksEndpoint::Financials { period, last }),
+        list_param().prop_map(|param| StocksEndpoint::List { param }),
+        Just(StocksEndpoint::Logo),
+        prop::option::of(1..NewsCount::MAX + 1)
+            .prop_map(|last| StocksEndpoint::News { last: last.and_then(NewsCount::new) }),
+        Just(StocksEndpoint::Ohlc),
+        Just(StocksEndpoint::Peers),
+        Just(StocksEndpoint::Previous),
+        Just(StocksEndpoint::Price),
+        Just(StocksEndpoint::Quote),
+        Just(StocksEndpoint::Relevant),
+        duration().prop_map(|duration| StocksEndpoint::Splits { duration }),
+        Just(StocksEndpoint::SectorPerformance),
+        Just(StocksEndpoint::Stats),
+        #[allow(deprecated)]
+        Just(StocksEndpoint::TimeSeries),
+        prop::option::of(duration()).prop_map(|date| StocksEndpoint::ThresholdSecurities { date }),
+        Just(StocksEndpoint::TodayEarnings),
+        Just(StocksEndpoint::TodayIpos),
+        Just(StocksEndpoint::UpcomingIpos),
+        Just(StocksEndpoint::VolumeByVenue),
+    ].boxed()
+}
+
+#[cfg(not(feature = "iex-cloud"))]
+fn endpoint() -> BoxedStrategy<StocksEndpoint<'static>> {
+    stock_endpoint()
+}
+
+#[cfg(feature = "iex-cloud")]
+fn endpoint() -> BoxedStrategy<StocksEndpoint<'static>> {
+    prop_oneof![
+        stock_endpoint(),
+        Just(StocksEndpoint::AdvancedStats),
+        statement().prop_map(|(period, last)| StocksEndpoint::Estimates { period, last }),
+        statement().prop_map(|(period, last)| StocksEndpoint::IncomeStatement { period, last }),
+        statement().prop_map(|(period, last)| StocksEndpoint::BalanceSheet { period, last }),
+        statement().prop_map(|(period, last)| StocksEndpoint::CashFlow { period, last }),
+        Just(StocksEndpoint::FundOwnership),
+        Just(StocksEndpoint::InstitutionalOwnership),
+        Just(StocksEndpoint::InsiderRoster),
+        Just(StocksEndpoint::InsiderTransactions),
+        Just(StocksEndpoint::PriceTarget),
+        Just(StocksEndpoint::RecommendationTrends),
+    ].boxed()
+}
+
+proptest! {
+    #[test]
+    fn endpoints_round_trip(endpoint in endpoint()) {
+        let path = endpoint.to_endpoint();
+        let parsed = StocksEndpoint::parse(&path).unwrap();
+        prop_assert_eq!(parsed.to_endpoint(), path);
+    }
+
+    #[test]
+    fn stock_paths_round_trip(symbol in "[a-z]{1,5}", endpoint in endpoint()) {
+        let path = format!("/stock/{}/{}", symbol, endpoint.to_endpoint());
+        let (parsed_symbol, parsed) = StocksEndpoint::from_path(&path).unwrap();
+        prop_assert_eq!(parsed_symbol, symbol.as_str());
+        prop_assert_eq!(format!("/stock/{}/{}", parsed_symbol, parsed.to_endpoint()), path);
+    }
+
+    #[test]
+    fn endpoints_serde_round_trip(endpoint in endpoint()) {
+        let json = serde_json::to_string(&endpoint).unwrap();
+        let parsed: StocksEndpoint = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed.to_endpoint(), endpoint.to_endpoint());
+    }
+}
+
+#[test]
+fn parses_chart_path_with_params() {
+    let (symbol, endpoint) = StocksEndpoint::from_path("/stock/aapl/chart/1m?chartLast=5").unwrap();
+    assert_eq!(symbol, "aapl");
+    assert_eq!(
+        endpoint,
+        StocksEndpoint::Chart {
+            duration: Duration::OneMonth,
+            params: Some(vec![ChartParam::Last(5)]),
+        }
+    );
+}
+
+#[test]
+fn parses_full_urls() {
+    let (symbol, endpoint) =
+        StocksEndpoint::from_path("https://api.iextrading.com/1.0/stock/market/list/gainers")
+            .unwrap();
+    assert_eq!(symbol, "market");
+    assert_eq!(
+        endpoint,
+        StocksEndpoint::List {
+            param: ListParam::Gainers,
+        }
+    );
+}
+
+#[test]
+fn stocks_paths_parse_from_strings_and_urls() {
+    let path: StocksPath = "/stock/aapl/chart/1m?chartLast=5".parse().unwrap();
+    assert_eq!(path.symbol(), "aapl");
+    assert_eq!(
+        path.endpoint(),
+        StocksEndpoint::Chart {
+            duration: Duration::OneMonth,
+            params: Some(vec![ChartParam::Last(5)]),
+        }
+    );
+
+    let url = Url::parse("https://api.iextrading.com/1.0/stock/spy/dividends/5y").unwrap();
+    let path = StocksPath::try_from(&url).unwrap();
+    assert_eq!(path.symbol(), "spy");
+    assert_eq!(path.endpoint().to_endpoint(), "dividends/5y");
+
+    let url = Url::parse("https://api.iextrading.com/1.0/ref-data/symbols").unwrap();
+    assert!(StocksPath::try_from(&url).is_err());
+}
+
+#[test]
+fn parses_statement_params_in_any_order() {
+    assert_eq!(
+        StocksEndpoint::parse("financials?last=4&period=annual").unwrap(),
+        StocksEndpoint::Financials {
+            period: Some(Period::Annual),
+            last: NonZeroUsize::new(4),
+        }
+    );
+    assert_eq!(
+        StocksEndpoint::Financials {
+            period: Some(Period::Quarter),
+            last: None,
+        }.to_endpoint(),
+        "financials?period=quarter"
+    );
+}
+
+#[cfg(not(feature = "iex-cloud"))]
+#[test]
+fn cloud_statements_need_the_cloud_feature() {
+    assert!(StocksEndpoint::parse("income").is_err());
+    assert!(StocksEndpoint::parse("balance-sheet?period=annual").is_err());
+    assert!(StocksEndpoint::parse("cash-flow").is_err());
+}
+
+#[test]
+fn collection_names_are_percent_encoded() {
+    let endpoint = StocksEndpoint::Collection {
+        collection: CollectionType::Sector,
+        name: "Health Care".into(),
+    };
+    assert_eq!(
+        endpoint.clone().to_endpoint(),
+        "collection/sector?collectionName=Health%20Care"
+    );
+    assert_eq!(
+        StocksEndpoint::parse("collection/sector?collectionName=Health+Care").unwrap(),
+        endpoint
+    );
+}
+
+#[test]
+fn rejects_unknown_endpoints() {
+    assert!(StocksEndpoint::parse("chart/1w").is_err());
+    assert!(StocksEndpoint::parse("quote?chartLast=5").is_err());
+    assert!(StocksEndpoint::parse("chart/1m?chartLast=five").is_err());
+    assert!(StocksEndpoint::parse("fundamentals").is_err());
+    assert!(StocksEndpoint::parse("financials?period=monthly").is_err());
+    assert!(StocksEndpoint::parse("financials?last=0").is_err());
+    assert!(StocksEndpoint::parse("cash-flow?chartLast=5").is_err());
+    assert!(StocksEndpoint::parse("collection/sector").is_err());
+    assert!(StocksEndpoint::parse("collection/industry?collectionName=Banks").is_err());
+    assert!(StocksEndpoint::parse("collection/tag?collectionName=%E").is_err());
+    assert!(StocksEndpoint::parse("news/last/0").is_err());
+    assert!(StocksEndpoint::parse("news/last/51").is_err());
+    assert!(StocksEndpoint::parse("news/last/-1").is_err());
+    assert!(StocksEndpoint::from_path("/stock//quote").is_err());
+    assert!(StocksEndpoint::from_path("/ref-data/symbols").is_err());
+}
+
+#[derive(Deserialize, Serialize)]
+struct Job {
+    symbols: Vec<String>,
+    endpoints: Vec<StocksEndpointBuf>,
+}
+
+#[test]
+fn endpoints_serialize_as_iex_paths() {
+    let endpoint = StocksEndpoint::Chart {
+        duration: Duration::OneYear,
+        params: Some(vec![ChartParam::Last(5), ChartParam::Reset(false)]),
+    };
+    assert_eq!(
+        serde_json::to_string(&endpoint).unwrap(),
+        r#""chart/1y?chartLast=5&chartReset=false""#
+    );
+    assert_eq!(
+        serde_json::to_string(&Duration::Date("20180129")).unwrap(),
+        r#""date/20180129""#
+    );
+    assert_eq!(serde_json::to_string(&ListParam::IexVolume).unwrap(), r#""iexvolume""#);
+    assert_eq!(
+        serde_json::to_string(&ChartParam::Interval(10)).unwrap(),
+        r#""chartInterval=10""#
+    );
+}
+
+#[test]
+fn jobs_load_from_toml() {
+    let job: Job = toml::from_str(
+        r#"
+        symbols = ["aapl", "msft"]
+        endpoints = ["chart/1y?chartLast=5", "dividends/5y", "list/gainers", "news/last/5"]
+        "#,
+    ).unwrap();
+
+    assert_eq!(job.symbols, vec!["aapl", "msft"]);
+    assert_eq!(
+        job.endpoints.iter().map(StocksEndpointBuf::endpoint).collect::<Vec<_>>(),
+        vec![
+            StocksEndpoint::Chart {
+                duration: Duration::OneYear,
+                params: Some(vec![ChartParam::Last(5)]),
+            },
+            StocksEndpoint::Dividends {
+                duration: Duration::FiveYears,
+            },
+            StocksEndpoint::List {
+                param: ListParam::Gainers,
+            },
+            StocksEndpoint::News {
+                last: NewsCount::new(5),
+            },
+        ]
+    );
+
+    let round_trip: Job = toml::from_str(&toml::to_string(&job).unwrap()).unwrap();
+    assert_eq!(round_trip.endpoints, job.endpoints);
+}
+
+#[test]
+fn endpoints_deserialize_borrowing_from_the_input() {
+    let json = r#"["chart/date/20180129", "splits/5y"]"#;
+    let endpoints: Vec<StocksEndpoint> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        endpoints,
+        vec![
+            StocksEndpoint::Chart {
+                duration: Duration::Date("20180129"),
+                params: None,
+            },
+            StocksEndpoint::Splits {
+                duration: Duration::FiveYears,
+            },
+        ]
+    );
+}
+
+#[test]
+fn invalid_endpoints_fail_to_load() {
+    let err = serde_json::from_str::<StocksEndpoint>(r#""chart/1w""#).unwrap_err();
+    assert!(err.to_string().contains("1w"), "{}", err);
+    let err = serde_json::from_str::<StocksEndpointBuf>(r#""chart/1w""#).unwrap_err();
+    assert!(err.to_string().contains("1w"), "{}", err);
+    assert!(serde_json::from_str::<ChartParam>(r#""chartLast""#).is_err());
+}
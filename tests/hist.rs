@@ -0,0 +1,256 @@
+//! Lists and downloads HIST files from a local HTTP server.
+extern crate iex;
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use iex::*;
+
+const FILE: &str = "20180102_IEXTP1_TOPS1.6.pcap.gz";
+
+fn content() -> Vec<u8> {
+    (0..10_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+/// Serves every connection with `handler`, which gets the server's base URL
+/// and the request head. Request heads are sent to the returned channel.
+fn serve<F>(handler: F) -> (String, mpsc::Receiver<String>)
+where
+    F: Fn(&str, &str) -> Vec<u8> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, requests) = mpsc::channel();
+    let base = base_url.clone();
+    thread::spawn(move || {
+        for socket in listener.incoming() {
+            let mut socket = socket.unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") && socket.read(&mut byte).unwrap() == 1 {
+                head.push(byte[0]);
+            }
+            let head = String::from_utf8(head).unwrap();
+            let _ = socket.write_all(&handler(&base, &head));
+            let _ = sender.send(head);
+        }
+    });
+    (base_url, requests)
+}
+
+fn response(status: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    ).into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Returns the start of the requested byte range, if any.
+fn range(head: &str) -> Option<usize> {
+    let head = head.to_lowercase();
+    let start = head.find("range: bytes=")? + "range: bytes=".len();
+    let end = head[start..].find('-')? + start;
+    head[start..end].parse().ok()
+}
+
+/// Serves the listing and the file, honouring ranges.
+fn hist_server(base: &str, head: &str) -> Vec<u8> {
+    if head.starts_with("GET /hist") {
+        let listing = format!(
+            r#"[{{"link": "{}/files/{}", "date": "20180102", "feed": "TOPS",
+                "version": "1.6", "protocol": "IEXTP1", "size": "10000"}}]"#,
+            base, FILE
+        );
+        return response("200 OK", listing.as_bytes());
+    }
+    match range(head) {
+        Some(start) => response("206 Partial Content", &content()[start..]),
+        None => response("200 OK", &content()),
+    }
+}
+
+fn archive_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("iex-hist-{}-{}", test, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn entry(client: &Client) -> HistEntry {
+    client.hist_date("20180102").unwrap().remove(0)
+}
+
+#[test]
+fn hist_lists_files_by_date() {
+    let (base_url, requests) = serve(hist_server);
+    let client = Client::new().with_base_url(base_url.clone());
+
+    let entry = entry(&client);
+    assert!(requests.recv().unwrap().starts_with("GET /hist?date=20180102 "));
+    assert_eq!(entry.link, format!("{}/files/{}", base_url, FILE));
+    assert_eq!(entry.feed, HistFeed::Tops);
+    assert_eq!(entry.size, 10000);
+}
+
+#[test]
+fn download_writes_the_file_and_catalogue() {
+    let (base_url, requests) = serve(hist_server);
+    let client = Client::new().with_base_url(base_url);
+    let entry = entry(&client);
+    let dir = archive_dir("download");
+
+    let mut archive = HistArchive::open(&dir).unwrap();
+    assert!(!archive.contains(&entry));
+    let path = archive.download(&entry).unwrap();
+    assert_eq!(path, dir.join(FILE));
+    assert_eq!(fs::read(&path).unwrap(), content());
+    assert!(!dir.join(format!("{}.part", FILE)).exists());
+
+    // The catalogue survives reopening, and downloaded files aren't fetched
+    // again.
+    let mut archive = HistArchive::open(&dir).unwrap();
+    assert!(archive.contains(&entry));
+    assert_eq!(archive.catalogue()[FILE].size, 10000);
+    archive.download(&entry).unwrap();
+    assert_eq!(requests.try_iter().count(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn download_resumes_a_partial_file() {
+    let (base_url, requests) = serve(hist_server);
+    let client = Client::new().with_base_url(base_url);
+    let entry = entry(&client);
+    let dir = archive_dir("resume");
+    let mut archive = HistArchive::open(&dir).unwrap();
+    fs::write(dir.join(format!("{}.part", FILE)), &content()[..4000]).unwrap();
+
+    let path = archive.download(&entry).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), content());
+    let head = requests.iter().nth(1).unwrap();
+    assert_eq!(range(&head), Some(4000));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn interrupted_download_is_resumed_by_the_next_attempt() {
+    // The first download is cut off after 3000 bytes.
+    let downloads = AtomicUsize::new(0);
+    let (base_url, _) = serve(move |base, head| {
+        if head.starts_with("GET /files/") && downloads.fetch_add(1, Ordering::SeqCst) == 0 {
+            let mut cut = response("200 OK", &content());
+            let head_len = cut.len() - content().len();
+            cut.truncate(head_len + 3000);
+            return cut;
+        }
+        hist_server(base, head)
+    });
+    let client = Client::new().with_base_url(base_url);
+    let entry = entry(&client);
+    let dir = archive_dir("interrupted");
+    let mut archive = HistArchive::open(&dir).unwrap();
+
+    // Depending on the HTTP client, the cut shows up as a short file or as a
+    // read error; either way the partial file is kept.
+    let err = archive.download(&entry).unwrap_err();
+    if let Some(mismatch) = err.downcast_ref::<SizeMismatch>() {
+        assert_eq!((mismatch.expected, mismatch.actual), (10000, 3000));
+    }
+    assert!(dir.join(format!("{}.part", FILE)).exists());
+    assert!(archive.catalogue().is_empty());
+
+    let path = archive.download(&entry).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), content());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn server_ignoring_the_range_restarts_the_download() {
+    let (base_url, _) = serve(|base, head| {
+        if head.starts_with("GET /files/") {
+            return response("200 OK", &content());
+        }
+        hist_server(base, head)
+    });
+    let client = Client::new().with_base_url(base_url);
+    let entry = entry(&client);
+    let dir = archive_dir("ignored-range");
+    let mut archive = HistArchive::open(&dir).unwrap();
+    fs::write(dir.join(format!("{}.part", FILE)), vec![0u8; 4000]).unwrap();
+
+    let path = archive.download(&entry).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), content());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unsafe_listings_are_not_downloaded() {
+    let (base_url, requests) = serve(|base, _| {
+        let listing = format!(
+            r#"[{{"link": "{0}/files/a", "date": "20180102", "feed": "TOPS",
+                 "version": "/../../escaped", "protocol": "IEXTP1", "size": "10000"}},
+                {{"link": "{0}/files/b", "date": "..", "feed": "DEEP",
+                 "version": "1.0", "protocol": "IEXTP1", "size": "10000"}},
+                {{"link": "{0}/files/c", "date": "20180102", "feed": "../x",
+                 "version": "1.0", "protocol": "IEXTP1", "size": "10000"}}]"#,
+            base
+        );
+        response("200 OK", listing.as_bytes())
+    });
+    let client = Client::new().with_base_url(base_url);
+    let entries = client.hist_date("20180102").unwrap();
+    assert!(requests.recv().unwrap().starts_with("GET /hist"));
+    let dir = archive_dir("unsafe");
+    let mut archive = HistArchive::open(&dir).unwrap();
+
+    for (entry, field) in entries.iter().zip(&["version", "date", "feed"]) {
+        assert!(!archive.contains(entry));
+        let err = archive.download(entry).unwrap_err();
+        assert_eq!(err.downcast::<InvalidHistValue>().unwrap().field, *field);
+    }
+    assert_eq!(requests.try_iter().count(), 0);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hist_dates_must_be_yyyymmdd() {
+    let client = Client::new().with_base_url("http://127.0.0.1:9");
+    for date in &["2018-01-02", "20180102&x=1", "../hist", ""] {
+        let err = client.hist_date(date).unwrap_err();
+        assert_eq!(err.downcast::<InvalidHistValue>().unwrap().field, "date");
+    }
+}
+
+#[test]
+fn missing_files_report_the_status() {
+    let (base_url, _) = serve(|base, head| {
+        if head.starts_with("GET /files/") {
+            return response("404 Not Found", b"");
+        }
+        hist_server(base, head)
+    });
+    let client = Client::new().with_base_url(base_url);
+    let entry = entry(&client);
+    let dir = archive_dir("missing");
+    let mut archive = HistArchive::open(&dir).unwrap();
+
+    let err = archive.download(&entry).unwrap_err();
+    assert_eq!(err.downcast::<UnexpectedStatus>().unwrap().status, 404);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
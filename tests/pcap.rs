@@ -0,0 +1,555 @@
+//! Decodes the synthetic captures in `tests/fixtures/pcap`, which
+//! `captures` writes.
+//!
+//! The `tops` captures hold the same two TOPS segments and a heartbeat, with
+//! a TCP packet between them, as pcap, gzipped pcap and pcap-ng. `deep.pcap`
+//! holds two DEEP segments of updates to one book, with messages
+//! milliseconds apart, and is replayed as streaming events too.
+#![cfg(feature = "pcap")]
+extern crate flate2;
+extern crate iex;
+extern crate serde_json;
+
+mod captures;
+
+use std::convert::TryInto;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Cursor, Read};
+use std::time::{Duration, Instant};
+
+use flate2::read::GzDecoder;
+use iex::*;
+
+use captures::{MS, T};
+
+fn money(s: &str) -> Money {
+    s.parse().unwrap()
+}
+
+/// Returns each segment's header fields and decoded messages, in Debug form
+/// since messages borrow from the capture.
+fn read(mut capture: Capture<Box<dyn Read>>) -> Vec<(u64, u16, Vec<String>)> {
+    let mut segments = Vec::new();
+    while let Some(segment) = capture.next_segment().unwrap() {
+        assert_eq!(segment.protocol, TOPS_PROTOCOL);
+        let messages = segment
+            .tops()
+            .map(|message| format!("{:?}", message.unwrap()))
+            .collect();
+        segments.push((segment.first_seq, segment.message_count, messages));
+    }
+    segments
+}
+
+#[test]
+fn captures_match_their_generator() {
+    type Generate = fn() -> Vec<u8>;
+    let captures: [(&str, Generate); 4] = [
+        ("tops.pcap", captures::tops_pcap),
+        ("tops.pcap.gz", captures::tops_pcap_gz),
+        ("tops.pcapng", captures::tops_pcapng),
+        ("deep.pcap", captures::deep_pcap),
+    ];
+    for &(name, generate) in &captures {
+        let path = format!("tests/fixtures/pcap/{}", name);
+        if env::var_os("IEX_REGENERATE_CAPTURES").is_some() {
+            fs::write(&path, generate()).unwrap();
+        }
+        // Compression output varies between encoders, so compare what the
+        // gzipped file holds.
+        let (mut written, mut generated) = (Vec::new(), Vec::new());
+        if name.ends_with(".gz") {
+            GzDecoder::new(File::open(&path).unwrap())
+                .read_to_end(&mut written)
+                .unwrap();
+            GzDecoder::new(&generate()[..])
+                .read_to_end(&mut generated)
+                .unwrap();
+        } else {
+            written = fs::read(&path).unwrap();
+            generated = generate();
+        }
+        assert!(written == generated, "{} differs from its generator", name);
+    }
+}
+
+#[test]
+fn pcap_gzip_and_pcapng_decode_alike() {
+    let pcap = read(Capture::open("tests/fixtures/pcap/tops.pcap").unwrap());
+    let gzipped = read(Capture::open("tests/fixtures/pcap/tops.pcap.gz").unwrap());
+    let pcapng = read(Capture::open("tests/fixtures/pcap/tops.pcapng").unwrap());
+    assert_eq!(pcap.len(), 3);
+    assert_eq!(
+        pcap.iter().map(|s| (s.0, s.1)).collect::<Vec<_>>(),
+        vec![(1, 6), (7, 5), (12, 0)]
+    );
+    assert_eq!(pcap, gzipped);
+    assert_eq!(pcap, pcapng);
+}
+
+#[test]
+fn tops_messages() {
+    let mut capture = Capture::open("tests/fixtures/pcap/tops.pcap").unwrap();
+    let segment = capture.next_segment().unwrap().unwrap();
+    let messages: Vec<TopsMessage> = segment.tops().map(Result::unwrap).collect();
+    assert_eq!(
+        messages,
+        vec![
+            TopsMessage::SystemEvent(SystemEventMessage {
+                event: SystemEventType::StartOfRegularMarketHours,
+                timestamp: T,
+            }),
+            TopsMessage::SecurityDirectory(SecurityDirectory {
+                flags: 0x20,
+                timestamp: T + 1,
+                symbol: "SPY",
+                round_lot_size: 100,
+                adjusted_poc_price: money("260.57"),
+                luld_tier: LuldTier::Tier1,
+            }),
+            TopsMessage::TradingStatus(TradingStatusMessage {
+                status: TradingStatus::Halted,
+                timestamp: T + 2,
+                symbol: "ZIEXT",
+                reason: "T1",
+            }),
+            TopsMessage::OperationalHalt(OperationalHalt {
+                status: OperationalHaltStatus::Halted,
+                timestamp: T + 3,
+                symbol: "ZIEXT",
+            }),
+            TopsMessage::ShortSalePriceTest(ShortSalePriceTest {
+                in_effect: true,
+                timestamp: T + 4,
+                symbol: "ZIEXT",
+                detail: ShortSaleDetail::Activated,
+            }),
+            TopsMessage::QuoteUpdate(QuoteUpdate {
+                flags: 0,
+                timestamp: T + 5,
+                symbol: "SPY",
+                bid_size: 9700,
+                bid_price: money("260.95"),
+                ask_price: money("260.96"),
+                ask_size: 1000,
+            }),
+        ]
+    );
+    match messages[1] {
+        TopsMessage::SecurityDirectory(ref directory) => {
+            assert!(directory.is_etp() && !directory.is_test_security())
+        }
+        _ => unreachable!(),
+    }
+
+    let segment = capture.next_segment().unwrap().unwrap();
+    let messages: Vec<TopsMessage> = segment.tops().map(Result::unwrap).collect();
+    let trade = TradeReport {
+        flags: 0x80,
+        timestamp: T + 6,
+        symbol: "SPY",
+        size: 100,
+        price: money("260.955"),
+        trade_id: 429974,
+    };
+    assert_eq!(messages[0], TopsMessage::TradeReport(trade.clone()));
+    assert_eq!(
+        messages[1],
+        TopsMessage::OfficialPrice(OfficialPrice {
+            price_type: OfficialPriceType::Opening,
+            timestamp: T + 7,
+            symbol: "SPY",
+            price: money("260.9"),
+        })
+    );
+    assert_eq!(
+        messages[2],
+        TopsMessage::TradeBreak(TradeReport {
+            flags: 0,
+            timestamp: T + 8,
+            ..trade
+        })
+    );
+    assert_eq!(
+        messages[3],
+        TopsMessage::AuctionInformation(AuctionInformation {
+            auction_type: AuctionType::Closing,
+            timestamp: T + 9,
+            symbol: "ZIEXT",
+            paired_shares: 50000,
+            reference_price: money("100"),
+            indicative_clearing_price: money("100.05"),
+            imbalance_shares: 1000,
+            imbalance_side: ImbalanceSide::Buy,
+            extension_number: 0,
+            scheduled_auction_time: 1_509_480_000,
+            auction_book_clearing_price: money("100.06"),
+            collar_reference_price: money("100"),
+            lower_auction_collar: money("90"),
+            upper_auction_collar: money("110"),
+        })
+    );
+    assert_eq!(
+        messages[4],
+        TopsMessage::Unknown {
+            message_type: b'z',
+            data: b"abc",
+        }
+    );
+    assert_eq!(messages[3].symbol(), Some("ZIEXT"));
+    assert_eq!(messages[4].timestamp(), None);
+}
+
+#[test]
+fn tops_messages_convert_to_json_types() {
+    let mut capture = Capture::open("tests/fixtures/pcap/tops.pcap").unwrap();
+    let segment = capture.next_segment().unwrap().unwrap();
+    let messages: Vec<TopsMessage> = segment.tops().map(Result::unwrap).collect();
+    match messages[0].clone() {
+        TopsMessage::SystemEvent(event) => {
+            let event = SystemEvent::from(event);
+            assert_eq!(event.system_event, SystemEventType::StartOfRegularMarketHours);
+            assert_eq!(event.timestamp, T / 1_000_000);
+        }
+        _ => unreachable!(),
+    }
+    match messages[5] {
+        TopsMessage::QuoteUpdate(ref quote) => {
+            assert_eq!((quote.bid().price, quote.bid().size), (money("260.95"), 9700));
+            assert_eq!((quote.ask().price, quote.ask().size), (money("260.96"), 1000));
+        }
+        _ => unreachable!(),
+    }
+
+    let segment = capture.next_segment().unwrap().unwrap();
+    let message = segment.tops().next().unwrap().unwrap();
+    match message {
+        TopsMessage::TradeReport(report) => {
+            let trade = Trade::from(report);
+            assert_eq!((trade.price, trade.size, trade.trade_id), (money("260.955"), 100, 429974));
+            assert!(trade.is_iso && !trade.is_odd_lot && !trade.is_outside_regular_hours);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn packets_keep_capture_timestamps() {
+    let mut capture = Capture::open("tests/fixtures/pcap/tops.pcapng").unwrap();
+    let mut timestamps = Vec::new();
+    let mut udp = 0;
+    while let Some(packet) = capture.next_packet().unwrap() {
+        timestamps.push(packet.timestamp);
+        udp += packet.udp_payload().is_some() as usize;
+    }
+    assert_eq!(timestamps, vec![T, T + 1000, T + 2000, T + 3000]);
+    assert_eq!(udp, 3);
+}
+
+#[test]
+fn truncated_captures_are_errors() {
+    let mut data = Vec::new();
+    File::open("tests/fixtures/pcap/tops.pcap")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    data.truncate(data.len() - 10);
+    let mut capture = Capture::from_reader(Cursor::new(data)).unwrap();
+    assert!(capture.next_segment().unwrap().is_some());
+    assert!(capture.next_segment().unwrap().is_some());
+    assert!(capture.next_segment().is_err());
+
+    assert!(Capture::from_reader(Cursor::new(b"not a capture".to_vec())).is_err());
+}
+
+#[test]
+fn oversized_pcapng_packets_are_errors() {
+    let u32_at = |data: &[u8], at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    let mut data = captures::tops_pcapng();
+    // Claim the first enhanced packet block holds 4 GiB.
+    let mut at = 0;
+    while u32_at(&data, at) != 6 {
+        at += u32_at(&data, at + 4) as usize;
+    }
+    data[at + 20..at + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut capture = Capture::from_reader(Cursor::new(data)).unwrap();
+    assert!(capture.next_packet().is_err());
+}
+
+#[test]
+fn deep_messages() {
+    let mut capture = Capture::open("tests/fixtures/pcap/deep.pcap").unwrap();
+    let mut messages = Vec::new();
+    while let Some(segment) = capture.next_segment().unwrap() {
+        assert_eq!(segment.protocol, DEEP_PROTOCOL);
+        for message in segment.deep() {
+            messages.push(format!("{:?}", message.unwrap()));
+        }
+    }
+    assert_eq!(messages.len(), 13);
+
+    let mut capture = Capture::open("tests/fixtures/pcap/deep.pcap").unwrap();
+    let segment = capture.next_segment().unwrap().unwrap();
+    let first: Vec<DeepFeedMessage> = segment.deep().map(Result::unwrap).collect();
+    assert_eq!(
+        first[4],
+        DeepFeedMessage::PriceLevelUpdate(PriceLevelUpdate {
+            side: Side::Buy,
+            flags: 1,
+            timestamp: T + 10 * MS,
+            symbol: "SPY",
+            size: 100,
+            price: money("260.95"),
+        })
+    );
+    assert_eq!(
+        first[2],
+        DeepFeedMessage::TradingStatus(TradingStatusMessage {
+            status: TradingStatus::Trading,
+            timestamp: T + 2 * MS,
+            symbol: "SPY",
+            reason: "",
+        })
+    );
+
+    let segment = capture.next_segment().unwrap().unwrap();
+    let second: Vec<DeepFeedMessage> = segment.deep().map(Result::unwrap).collect();
+    assert_eq!(
+        second[0],
+        DeepFeedMessage::SecurityEvent(SecurityEvent {
+            event: SecurityEventType::OpeningProcessComplete,
+            timestamp: T + 12 * MS,
+            symbol: "SPY",
+        })
+    );
+    match second[4] {
+        DeepFeedMessage::TradeBreak(ref broken) => assert_eq!(broken.trade_id, 1),
+        ref message => panic!("expected a trade break, got {:?}", message),
+    }
+    assert_eq!(second[6].symbol(), Some("SPY"));
+    assert_eq!(second[6].timestamp(), Some(T + 17 * MS));
+}
+
+#[test]
+fn price_level_updates_convert_to_book_levels() {
+    let mut capture = Capture::open("tests/fixtures/pcap/deep.pcap").unwrap();
+    let mut bids: Vec<Bid> = Vec::new();
+    let mut asks: Vec<Ask> = Vec::new();
+    while let Some(segment) = capture.next_segment().unwrap() {
+        for message in segment.deep() {
+            if let DeepFeedMessage::PriceLevelUpdate(update) = message.unwrap() {
+                bids.extend(update.bid());
+                asks.extend(update.ask());
+            }
+        }
+    }
+    assert_eq!(
+        bids.iter().map(|bid| (bid.price, bid.size)).collect::<Vec<_>>(),
+        vec![
+            (money("260.94"), 200),
+            (money("260.95"), 100),
+            (money("260.93"), 500),
+        ]
+    );
+    assert_eq!(
+        asks.iter().map(|ask| (ask.price, ask.size)).collect::<Vec<_>>(),
+        vec![
+            (money("260.97"), 300),
+            (money("260.97"), 0),
+            (money("260.98"), 400),
+        ]
+    );
+    assert_eq!(bids[0].timestamp, (T + 10 * MS) / MS);
+}
+
+#[test]
+fn order_books_from_deep() {
+    let mut capture = Capture::open("tests/fixtures/pcap/deep.pcap").unwrap();
+    let mut books = OrderBooks::new();
+    while let Some(segment) = capture.next_segment().unwrap() {
+        for message in segment.deep() {
+            books.apply(&message.unwrap());
+        }
+    }
+    let book = books.get("SPY").unwrap();
+    let bids: Vec<(Money, u64)> = book.bids(5).iter().map(|b| (b.price, b.size)).collect();
+    let asks: Vec<(Money, u64)> = book.asks(5).iter().map(|a| (a.price, a.size)).collect();
+    // The last buy update's event never completes, so it isn't applied.
+    assert!(book.in_transition());
+    assert_eq!(bids, vec![(money("260.95"), 100), (money("260.94"), 200)]);
+    assert_eq!(asks, vec![(money("260.98"), 400)]);
+    assert_eq!(book.best_bid().unwrap().price, money("260.95"));
+    assert_eq!(book.bids(1).len(), 1);
+    assert_eq!(book.trading_status(), Some(TradingStatus::Halted));
+    assert_eq!(book.trades().count(), 0);
+    assert_eq!(book.timestamp(), (T + 17 * MS) / MS);
+
+    let quote: Quote = serde_json::from_str(include_str!("fixtures/quote.json")).unwrap();
+    let system_event = SystemEvent {
+        system_event: SystemEventType::StartOfMessages,
+        timestamp: T / 1_000_000,
+        extra: Default::default(),
+    };
+    let book = book.snapshot(1).into_book(quote, system_event);
+    assert_eq!(book.bids.len(), 1);
+    assert_eq!(book.asks[0].size, 400);
+}
+
+#[test]
+fn order_books_from_the_deep_stream() {
+    let data = r#"[
+        {"symbol": "SPY", "messageType": "book", "data": {
+            "bids": [{"price": 260.95, "size": 100, "timestamp": 1000}],
+            "asks": [{"price": 260.97, "size": 300, "timestamp": 1001}]
+        }},
+        {"symbol": "SPY", "messageType": "trades", "data": [
+            {"price": 260.96, "size": 100, "tradeId": 7, "isISO": false, "isOddLot": false,
+             "isOutsideRegularHours": false, "isSinglePriceCross": false,
+             "isTradeThroughExempt": false, "timestamp": 1002}
+        ]},
+        {"symbol": "SPY", "messageType": "tradingStatus",
+         "data": {"status": "P", "reason": "MCB3", "timestamp": 1003}}
+    ]"#;
+    let mut books = OrderBooks::new();
+    for event in decode_events(Channel::Deep, data).unwrap() {
+        books.apply_event(&event);
+    }
+    let book = books.get("SPY").unwrap();
+    assert_eq!(book.best_bid().unwrap().price, money("260.95"));
+    assert_eq!(book.best_ask().unwrap().size, 300);
+    assert_eq!(book.trades().map(|t| t.trade_id).collect::<Vec<_>>(), vec![7]);
+    assert_eq!(book.trading_status(), Some(TradingStatus::Paused));
+}
+
+#[test]
+fn hist_replays_as_the_deep_stream() {
+    let replay = Replay::hist("tests/fixtures/pcap/deep.pcap", Channel::Deep)
+        .unwrap()
+        .with_speed(Speed::AsFastAsPossible);
+    let clock = replay.clock();
+    let mut books = OrderBooks::new();
+    let mut kinds = Vec::new();
+    let mut statuses = Vec::new();
+    for event in replay {
+        let event = event.unwrap();
+        books.apply_event(&event);
+        kinds.push(match event {
+            Event::SystemEvent { .. } => "systemEvent".to_string(),
+            Event::Trade { .. } => "trades".to_string(),
+            Event::Deep(DeepMessage {
+                data: DeepData::TradingStatus(ref status),
+                ref message_type,
+                ..
+            }) => {
+                statuses.push((status.status.clone(), status.reason.clone()));
+                message_type.clone()
+            }
+            Event::Deep(message) => message.message_type,
+            event => panic!("unexpected {:?}", event),
+        });
+    }
+    assert_eq!(
+        kinds,
+        vec![
+            "systemEvent",
+            "tradingStatus",
+            "book",
+            "book",
+            "securityEvent",
+            "book",
+            "trades",
+            "tradeBreak",
+            "tradingStatus",
+        ]
+    );
+    assert_eq!(clock.now(), Some(T / MS + 17));
+    assert_eq!(
+        statuses,
+        vec![
+            ("T".to_string(), String::new()),
+            ("H".to_string(), "T1".to_string()),
+        ]
+    );
+
+    // The streamed book matches one built from the binary messages.
+    let book = books.get("SPY").unwrap();
+    let bids: Vec<(Money, u64)> = book.bids(5).iter().map(|b| (b.price, b.size)).collect();
+    assert_eq!(bids, vec![(money("260.95"), 100), (money("260.94"), 200)]);
+    assert_eq!(book.best_ask().unwrap().price, money("260.98"));
+    assert_eq!(book.trades().count(), 0);
+    assert_eq!(book.trading_status(), Some(TradingStatus::Halted));
+}
+
+#[test]
+fn hist_replays_as_tops_and_last() {
+    let replay = |channel| {
+        Replay::hist("tests/fixtures/pcap/tops.pcap", channel)
+            .unwrap()
+            .with_speed(Speed::AsFastAsPossible)
+            .map(Result::unwrap)
+            .collect::<Vec<Event>>()
+    };
+
+    let tops: Vec<Tops> = replay(Channel::Tops)
+        .into_iter()
+        .map(|event| match event {
+            Event::Tops(tops) => tops,
+            event => panic!("expected TOPS, got {:?}", event),
+        })
+        .collect();
+    assert_eq!(tops.len(), 2);
+    assert_eq!((tops[0].bid_price, tops[0].ask_size), (money("260.95"), 1000));
+    assert_eq!(tops[0].seq, Some(6));
+    assert_eq!((tops[1].last_sale_price, tops[1].volume), (money("260.955"), 100));
+    assert_eq!(tops[1].bid_price, money("260.95"));
+
+    match &replay(Channel::Last)[..] {
+        [Event::Last(sale)] => assert_eq!((sale.symbol.as_str(), sale.seq), ("SPY", Some(7))),
+        events => panic!("expected one last sale, got {:?}", events),
+    }
+
+    // DEEP files give TOPS from the book.
+    let tops = Replay::hist("tests/fixtures/pcap/deep.pcap", Channel::Tops)
+        .unwrap()
+        .with_speed(Speed::AsFastAsPossible)
+        .filter_map(|event| match event.unwrap() {
+            Event::Tops(tops) => Some(tops),
+            _ => None,
+        })
+        .last()
+        .unwrap();
+    assert_eq!((tops.bid_price, tops.ask_price), (money("260.95"), money("260.98")));
+}
+
+#[test]
+fn hist_replay_filters() {
+    let events = |replay: Replay| replay.map(Result::unwrap).count();
+    let deep = || {
+        Replay::hist("tests/fixtures/pcap/deep.pcap", Channel::Deep)
+            .unwrap()
+            .with_speed(Speed::AsFastAsPossible)
+    };
+    // System events are about every symbol.
+    assert_eq!(events(deep().with_symbols(&["AAPL"])), 1);
+    assert_eq!(events(deep().with_symbols(&["spy"])), 9);
+    let start = T / MS;
+    assert_eq!(events(deep().ending_at(start - 1)), 0);
+    assert_eq!(events(deep().starting_at(start + 18)), 0);
+
+    // From the first book update to the trade, in milliseconds from `T`.
+    let times: Vec<u64> = deep()
+        .starting_at(start + 10)
+        .ending_at(start + 14)
+        .map(|event| event.unwrap().timestamp().unwrap() - start)
+        .collect();
+    assert_eq!(times, vec![10, 11, 12, 13, 14]);
+
+    // In real time, the replay takes as long as the messages span.
+    let began = Instant::now();
+    assert_eq!(events(deep().with_speed(Speed::Multiplier(1.0))), 9);
+    assert!(began.elapsed() >= Duration::from_millis(17));
+
+    let err = Replay::hist("tests/fixtures/pcap/deep.pcap", Channel::News).err();
+    assert!(err.unwrap().downcast::<UnsupportedChannel>().is_ok());
+}
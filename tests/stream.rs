@@ -0,0 +1,318 @@
+//! Streams from a local Server-Sent Events server.
+#![cfg(feature = "iex-cloud")]
+#[cfg(feature = "async")]
+extern crate futures;
+extern crate iex;
+#[macro_use]
+extern crate serde_json;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use iex::*;
+
+/// Serves one request with `response`, returning the base URL and a channel
+/// that receives the request head.
+fn serve(response: String) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, requests) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") && socket.read(&mut byte).unwrap() == 1 {
+            head.push(byte[0]);
+        }
+        // Tests that ignore the request drop the receiver.
+        let _ = sender.send(String::from_utf8(head).unwrap());
+        socket.write_all(response.as_bytes()).unwrap();
+    });
+    (base_url, requests)
+}
+
+fn sse(events: &[String]) -> String {
+    let mut response = String::from(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+    );
+    response.push_str(":heartbeat\n\n");
+    for data in events {
+        response.push_str(&format!("data: {}\n\n", data));
+    }
+    response
+}
+
+fn quotes() -> String {
+    let quote: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/quote.json")).unwrap();
+    serde_json::Value::Array(vec![quote]).to_string()
+}
+
+fn tops() -> String {
+    tops_with(12, 1494419730000)
+}
+
+fn tops_with(seq: u64, last_updated: u64) -> String {
+    json!([{
+        "symbol": "SNAP",
+        "sector": "mediaentertainment",
+        "securityType": "commonstock",
+        "bidPrice": 15.52,
+        "bidSize": 300,
+        "askPrice": 15.53,
+        "askSize": 200,
+        "lastUpdated": last_updated,
+        "lastSalePrice": 15.52,
+        "lastSaleSize": 100,
+        "lastSaleTime": 1494419729000u64,
+        "volume": 2772,
+        "marketPercent": 0.01028,
+        "seq": seq,
+    }]).to_string()
+}
+
+#[test]
+fn quotes_stream_from_local_server() {
+    let (base_url, requests) = serve(sse(&[quotes(), "[]".to_string(), quotes()]));
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let events: Vec<Event> = client
+        .stream(Channel::Quotes, &["aapl", "brk.b"])
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+    match events[0] {
+        Event::Quote(ref quote) => assert_eq!(quote.symbol, "AAPL"),
+        ref event => panic!("expected a quote, got {:?}", event),
+    }
+
+    let head = requests.recv().unwrap();
+    assert!(head.starts_with("GET /stocksUS?symbols=aapl,brk.b&token=token "));
+    assert!(head.to_lowercase().contains("accept: text/event-stream"));
+}
+
+#[test]
+fn tops_stream_from_local_server() {
+    let (base_url, _) = serve(sse(&[tops()]));
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let mut stream = client.stream(Channel::Tops, &["snap"]).unwrap();
+    match stream.next().unwrap().unwrap() {
+        Event::Tops(tops) => {
+            assert_eq!(tops.symbol, "SNAP");
+            assert_eq!(tops.bid_size, 300);
+            assert_eq!(tops.seq, Some(12));
+        }
+        event => panic!("expected TOPS, got {:?}", event),
+    }
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn invalid_data_is_an_error_item() {
+    let (base_url, _) = serve(sse(&["not json".to_string(), tops()]));
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let events: Vec<Result<Event>> = client.stream(Channel::Tops, &["snap"]).unwrap().collect();
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_err());
+    assert!(events[1].is_ok());
+}
+
+#[test]
+fn refused_streams_report_the_status() {
+    let (base_url, _) = serve("HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\n".to_string());
+    let client = Client::cloud("bad token").with_sse_base_url(base_url);
+
+    let err = client.stream(Channel::Quotes, &["aapl"]).err().unwrap();
+    assert_eq!(err.downcast::<StreamRefused>().unwrap().status, 403);
+}
+
+#[test]
+fn deep_streams_request_the_deep_channel() {
+    let client = Client::cloud("token").with_sse_base_url("http://localhost/");
+    assert_eq!(
+        client.stream_url(Channel::Deep, &["snap"]),
+        "http://localhost/deep?symbols=snap&channels=deep&token=token"
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_stream_from_local_server() {
+    use futures::Stream;
+
+    let (base_url, _) = serve(sse(&[quotes(), quotes()]));
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let events: Vec<Event> = client
+        .stream(Channel::Quotes, &["aapl"])
+        .unwrap()
+        .into_async()
+        .wait()
+        .map(|event| event.unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+}
+
+/// Serves one connection per script, then cuts it. A `None` script takes the
+/// server down for a while instead, so connection attempts fail.
+fn chaos(scripts: Vec<Option<String>>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, requests) = mpsc::channel();
+    thread::spawn(move || {
+        let mut listener = Some(listener);
+        for script in scripts {
+            let response = match script {
+                Some(response) => response,
+                None => {
+                    drop(listener.take());
+                    thread::sleep(Duration::from_millis(200));
+                    listener = Some(TcpListener::bind(addr).unwrap());
+                    continue;
+                }
+            };
+            let (mut socket, _) = listener.as_ref().unwrap().accept().unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") && socket.read(&mut byte).unwrap() == 1 {
+                head.push(byte[0]);
+            }
+            let _ = sender.send(String::from_utf8(head).unwrap());
+            let _ = socket.write_all(response.as_bytes());
+        }
+    });
+    (format!("http://{}", addr), requests)
+}
+
+fn backoff() -> Backoff {
+    Backoff {
+        initial: Duration::from_millis(10),
+        max: Duration::from_millis(40),
+        multiplier: 2,
+        attempts: None,
+    }
+}
+
+#[test]
+fn subscription_survives_server_dying_mid_stream() {
+    let mut cut = sse(&[tops_with(1, 1000), tops_with(2, 2000)]);
+    cut.push_str("data: [{\"symbol\":");
+    // After the cut, the server goes down, then closes a connection at once.
+    let (base_url, requests) = chaos(vec![
+        Some(cut),
+        None,
+        Some(sse(&[])),
+        Some(sse(&[tops_with(5, 5000)])),
+    ]);
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let events: Vec<Event> = client
+        .subscribe(Channel::Tops, &["snap"], backoff())
+        .take(4)
+        .map(|event| event.unwrap())
+        .collect();
+    let seqs: Vec<Option<u64>> = events.iter().map(Event::seq).collect();
+    assert_eq!(seqs, vec![Some(1), Some(2), None, Some(5)]);
+    // One gap covers the outage, up to the first event after it.
+    match events[2] {
+        Event::Gap(ref gap) => assert_eq!(
+            *gap,
+            Gap {
+                reason: GapReason::Disconnected,
+                symbol: "SNAP".to_string(),
+                since: Some(2000),
+                until: Some(5000),
+                missed: Some(2),
+            }
+        ),
+        ref event => panic!("expected a gap, got {:?}", event),
+    }
+
+    // Every connection subscribes to the same symbols.
+    for _ in 0..3 {
+        assert!(requests.recv().unwrap().starts_with("GET /tops?symbols=snap&token=token "));
+    }
+}
+
+#[test]
+fn subscription_retries_server_errors() {
+    let unavailable = "HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\n\r\n";
+    let (base_url, _) = chaos(vec![Some(unavailable.to_string()), Some(sse(&[tops()]))]);
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let mut subscription = client.subscribe(Channel::Tops, &["snap"], backoff());
+    match subscription.next().unwrap().unwrap() {
+        Event::Tops(tops) => assert_eq!(tops.symbol, "SNAP"),
+        event => panic!("expected TOPS without a gap, got {:?}", event),
+    }
+}
+
+#[test]
+fn subscription_stops_when_refused() {
+    let unauthorized = "HTTP/1.1 401 Unauthorized\r\nConnection: close\r\n\r\n";
+    let (base_url, _) = chaos(vec![Some(unauthorized.to_string())]);
+    let client = Client::cloud("bad token").with_sse_base_url(base_url);
+
+    let mut subscription = client.subscribe(Channel::Tops, &["snap"], backoff());
+    let err = subscription.next().unwrap().unwrap_err();
+    assert_eq!(err.downcast::<StreamRefused>().unwrap().status, 401);
+    assert!(subscription.next().is_none());
+}
+
+#[test]
+fn subscription_gives_up_after_its_attempts() {
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let client = Client::cloud("token").with_sse_base_url(format!("http://{}", addr));
+
+    let backoff = Backoff {
+        attempts: Some(3),
+        ..backoff()
+    };
+    let mut subscription = client.subscribe(Channel::Tops, &["snap"], backoff);
+    assert!(subscription.next().unwrap().is_err());
+    assert!(subscription.next().is_none());
+}
+
+#[test]
+fn subscription_gives_up_on_connections_that_close_at_once() {
+    let empty = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n";
+    let (base_url, requests) = chaos(vec![Some(empty.to_string()); 5]);
+    let client = Client::cloud("token").with_sse_base_url(base_url);
+
+    let backoff = Backoff {
+        attempts: Some(3),
+        ..backoff()
+    };
+    let mut subscription = client.subscribe(Channel::Tops, &["snap"], backoff);
+    let err = subscription.next().unwrap().unwrap_err();
+    assert!(err.downcast::<StreamClosed>().is_ok());
+    assert!(subscription.next().is_none());
+    assert_eq!(requests.try_iter().count(), 3);
+}
+
+#[test]
+fn replay_of_a_recorded_stream() {
+    let path = std::env::temp_dir().join(format!("iex-replay-{}.sse", std::process::id()));
+    let body = [tops_with(1, 1000), tops_with(2, 1100), tops_with(3, 1200)];
+    let body: String = body.iter().map(|data| format!("data: {}\n\n", data)).collect();
+    std::fs::write(&path, body).unwrap();
+
+    let replay = Replay::recorded(&path, Channel::Tops)
+        .unwrap()
+        .with_speed(Speed::Multiplier(10.0))
+        .starting_at(1100);
+    let clock = replay.clock();
+    let began = std::time::Instant::now();
+    let times: Vec<u64> = replay.map(|event| event.unwrap().timestamp().unwrap()).collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(times, vec![1100, 1200]);
+    assert_eq!(clock.now(), Some(1200));
+    assert!(began.elapsed() >= Duration::from_millis(10));
+}
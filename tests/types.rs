@@ -0,0 +1,263 @@
+//! Deserializes saved IEX responses from `tests/fixtures`.
+//!
+//! These run against both `Money` representations, so run them with and
+//! without `--features decimal`.
+extern crate iex;
+extern crate serde;
+extern crate serde_json;
+
+use iex::*;
+use std::collections::BTreeMap;
+
+fn money(s: &str) -> Money {
+    s.parse().unwrap()
+}
+
+#[test]
+fn quote_fixture() {
+    let quote: Quote = serde_json::from_str(include_str!("fixtures/quote.json")).unwrap();
+    assert_eq!(quote.symbol, "AAPL");
+    assert_eq!(quote.open, Some(money("171.18")));
+    assert_eq!(quote.high, Some(money("172.68")));
+    assert_eq!(quote.latest_price, money("170.31"));
+    assert_eq!(quote.delayed_price, Some(money("170.275")));
+    assert_eq!(quote.change, Some(money("5.37")));
+    assert_eq!(quote.market_cap, Some(money("864146098320")));
+    assert_eq!(quote.latest_volume, Some(14817843));
+    assert_eq!(quote.avg_total_volume, Some(36207812));
+    assert_eq!(quote.calculation_price, CalculationPrice::Tops);
+    assert_eq!(quote.latest_source, LatestSource::IexRealTimePrice);
+}
+
+#[test]
+fn book_fixture() {
+    let book: Book = serde_json::from_str(include_str!("fixtures/book.json")).unwrap();
+    assert_eq!(book.bids[0].price, money("170.3"));
+    assert_eq!(book.asks[0].price, money("170.33"));
+    assert_eq!(book.trades[1].price, money("170.305"));
+    assert_eq!(book.quote.iex_ask_price, Some(money("170.33")));
+    assert_eq!(book.bids[1].size, 300);
+    assert_eq!(book.trades[0].trade_id, 517341294);
+    assert_eq!(book.system_event.timestamp, 1521811800001);
+    assert_eq!(
+        book.system_event.system_event,
+        SystemEventType::StartOfRegularMarketHours
+    );
+}
+
+#[test]
+fn ohlc_fixture() {
+    let ohlc: OHLC = serde_json::from_str(include_str!("fixtures/ohlc.json")).unwrap();
+    assert_eq!(ohlc.open.price, money("171.18"));
+    assert_eq!(ohlc.close.price, money("164.94"));
+    assert_eq!(ohlc.low, Some(money("168.6")));
+}
+
+#[test]
+fn previous_fixture() {
+    let previous: Previous = serde_json::from_str(include_str!("fixtures/previous.json")).unwrap();
+    assert_eq!(previous.close, money("164.94"));
+    assert_eq!(previous.change, money("-6.33"));
+    assert_eq!(previous.vwap, money("166.6417"));
+}
+
+#[test]
+fn chart_fixture() {
+    let chart: Vec<ChartDataPoint> =
+        serde_json::from_str(include_str!("fixtures/chart.json")).unwrap();
+    assert_eq!(chart.len(), 2);
+    assert_eq!(chart[0].high, money("175.09"));
+    assert_eq!(chart[1].change, money("-6.33"));
+    assert_eq!(chart[0].unadjusted_volume, 35247358);
+    let volume: u64 = chart.iter().map(|point| point.volume).sum();
+    assert_eq!(volume, 76298434);
+}
+
+#[test]
+fn company_fixture() {
+    let company: Company = serde_json::from_str(include_str!("fixtures/company.json")).unwrap();
+    assert_eq!(company.issue_type, IssueType::CommonStock);
+}
+
+#[test]
+fn dividends_fixture() {
+    let dividends: Vec<Dividend> =
+        serde_json::from_str(include_str!("fixtures/dividends.json")).unwrap();
+    assert_eq!(dividends[0].dtype, Some(DividendType::DividendIncome));
+    assert_eq!(dividends[0].qualified, Some(DividendQualified::Qualified));
+    assert_eq!(dividends[0].flag, Some(DividendFlag::Unknown(String::new())));
+    assert_eq!(dividends[1].flag, Some(DividendFlag::Unknown("XX".to_string())));
+}
+
+#[test]
+fn earnings_fixture() {
+    let earnings: Earnings = serde_json::from_str(include_str!("fixtures/earnings.json")).unwrap();
+    assert_eq!(earnings.earnings[0].announce_time, AnnounceTime::AfterClose);
+    assert_eq!(earnings.earnings[1].number_of_estimates, Some(15));
+}
+
+#[test]
+fn symbols_fixture() {
+    let symbols: Vec<Symbol> = serde_json::from_str(include_str!("fixtures/symbols.json")).unwrap();
+    assert_eq!(symbols[0].symbol_type, IssueType::CommonStock);
+    assert_eq!(symbols[1].symbol_type, IssueType::ExchangeTradedFund);
+    assert_eq!(symbols[2].symbol_type, IssueType::Unknown("N/A".to_string()));
+}
+
+#[test]
+fn statement_fixtures() {
+    let income: IncomeStatements =
+        serde_json::from_str(include_str!("fixtures/income.json")).unwrap();
+    assert_eq!(income.income[0].net_income, Some(13855000000.0));
+    assert_eq!(income.income[0].currency, Some("USD".to_string()));
+
+    let balance: BalanceSheets =
+        serde_json::from_str(include_str!("fixtures/balance_sheet.json")).unwrap();
+    assert_eq!(balance.balancesheet[0].total_assets, Some(365725000000.0));
+    assert_eq!(balance.balancesheet[0].goodwill, None);
+
+    let cash: CashFlows = serde_json::from_str(include_str!("fixtures/cash_flow.json")).unwrap();
+    assert_eq!(cash.cashflow[0].changes_in_receivables, Some(-9082000000.0));
+    assert_eq!(cash.cashflow[0].exchange_rate_effect, None);
+}
+
+#[test]
+fn market_fixtures() {
+    let ohlc: MarketOhlc = serde_json::from_str(include_str!("fixtures/market_ohlc.json")).unwrap();
+    assert_eq!(ohlc["SPY"].open.price, money("264.17"));
+    assert_eq!(ohlc["ZXIET"].high, None);
+
+    let previous: MarketPrevious =
+        serde_json::from_str(include_str!("fixtures/market_previous.json")).unwrap();
+    assert_eq!(previous["DBX"].volume, 38564350);
+}
+
+#[test]
+fn sector_performance_fixture() {
+    let sectors: Vec<SectorPerformance> =
+        serde_json::from_str(include_str!("fixtures/sector_performance.json")).unwrap();
+    assert_eq!(sectors[1].name, "Health Care");
+    assert_eq!(sectors[1].performance, -0.00228);
+}
+
+#[test]
+fn ipo_calendar_fixture() {
+    let ipos: IpoCalendar =
+        serde_json::from_str(include_str!("fixtures/upcoming_ipos.json")).unwrap();
+    assert_eq!(ipos.raw_data[0].lead_underwriters.len(), 2);
+    assert_eq!(ipos.raw_data[0].price_high, Some(money("15")));
+    assert_eq!(ipos.raw_data[1].lead_underwriters, Vec::<String>::new());
+    assert_eq!(ipos.raw_data[1].shares_offered, Some(20000000));
+    assert_eq!(ipos.view_data[0].price, "$12.00 - 15.00");
+}
+
+#[test]
+fn today_earnings_fixture() {
+    let earnings: TodayEarnings =
+        serde_json::from_str(include_str!("fixtures/today_earnings.json")).unwrap();
+    assert!(earnings.bto.is_empty());
+    assert_eq!(earnings.amc[0].symbol, "AAPL");
+    assert_eq!(earnings.amc[0].quote.symbol, "AAPL");
+    assert_eq!(earnings.amc[0].earning.actual_eps, Some(2.34));
+    assert!(earnings.amc[0].earning.extra.is_empty());
+}
+
+#[test]
+fn crypto_fixture() {
+    let quotes: Vec<CryptoQuote> =
+        serde_json::from_str(include_str!("fixtures/crypto.json")).unwrap();
+    assert_eq!(quotes[0].calculation_price, CalculationPrice::RealTime);
+    assert_eq!(quotes[0].latest_source, LatestSource::RealTimePrice);
+    assert_eq!(quotes[0].primary_exchange, None);
+    assert_eq!(quotes[0].latest_volume, Some(25612.73519));
+    assert_eq!(quotes[0].ask_price, Some(money("6399.53")));
+    assert_eq!(quotes[0].pe_ratio, None);
+}
+
+#[test]
+fn news_fixture() {
+    let news: Vec<News> = serde_json::from_str(include_str!("fixtures/news.json")).unwrap();
+    assert_eq!(news[0].datetime.timestamp(), 1533065520);
+    assert_eq!(news[0].related[0], "AAPL");
+    assert_eq!(news[0].lang, Some("en".to_string()));
+    assert_eq!(news[0].has_paywall, Some(false));
+    assert_eq!(news[1].datetime.timestamp(), 1533063000);
+    assert_eq!(news[1].related, vec!["AAPL", "QCOM"]);
+    assert_eq!(news[1].image, None);
+}
+
+#[test]
+fn news_round_trips_through_json() {
+    let news: Vec<News> = serde_json::from_str(include_str!("fixtures/news.json")).unwrap();
+    let json = serde_json::to_string(&news).unwrap();
+    let read_back: Vec<News> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read_back[1].related, vec!["AAPL", "QCOM"]);
+    assert_eq!(read_back[1].datetime, news[1].datetime);
+    assert_eq!(read_back[0].headline, news[0].headline);
+}
+
+#[test]
+fn hist_fixture() {
+    let hist: BTreeMap<String, Vec<HistEntry>> =
+        serde_json::from_str(include_str!("fixtures/hist.json")).unwrap();
+    let entry = &hist["20180102"][1];
+    assert_eq!(entry.feed, HistFeed::Tops);
+    assert_eq!(entry.size, 3042914624);
+    assert_eq!(entry.file_name().unwrap(), "20180102_IEXTP1_TOPS1.6.pcap.gz");
+    assert_eq!(hist["20180103"].len(), 1);
+}
+
+#[test]
+fn unknown_enum_values_round_trip() {
+    let dividends: Vec<Dividend> =
+        serde_json::from_str(include_str!("fixtures/dividends.json")).unwrap();
+    let value = serde_json::to_value(&dividends[1]).unwrap();
+    assert_eq!(value["flag"], "XX");
+    assert_eq!(value["type"], "Dividend income");
+}
+
+#[test]
+fn fixtures_are_fully_modelled() {
+    fn unknown<T>(json: &str) -> Vec<String>
+    where
+        T: for<'de> serde::Deserialize<'de> + serde::Serialize,
+    {
+        let payload = serde_json::from_str(json).unwrap();
+        schema_report::<T>(&payload).unwrap().unknown
+    }
+
+    assert!(unknown::<Quote>(include_str!("fixtures/quote.json")).is_empty());
+    assert!(unknown::<Book>(include_str!("fixtures/book.json")).is_empty());
+    assert!(unknown::<OHLC>(include_str!("fixtures/ohlc.json")).is_empty());
+    assert!(unknown::<Previous>(include_str!("fixtures/previous.json")).is_empty());
+    assert!(unknown::<Vec<ChartDataPoint>>(include_str!("fixtures/chart.json")).is_empty());
+    assert!(unknown::<Company>(include_str!("fixtures/company.json")).is_empty());
+    assert!(unknown::<Vec<Dividend>>(include_str!("fixtures/dividends.json")).is_empty());
+    assert!(unknown::<Earnings>(include_str!("fixtures/earnings.json")).is_empty());
+    assert!(unknown::<Vec<Symbol>>(include_str!("fixtures/symbols.json")).is_empty());
+    assert!(unknown::<Vec<News>>(include_str!("fixtures/news.json")).is_empty());
+    assert!(unknown::<Vec<CryptoQuote>>(include_str!("fixtures/crypto.json")).is_empty());
+    assert!(
+        unknown::<Vec<SectorPerformance>>(include_str!("fixtures/sector_performance.json"))
+            .is_empty()
+    );
+    assert!(unknown::<IpoCalendar>(include_str!("fixtures/upcoming_ipos.json")).is_empty());
+    assert!(unknown::<TodayEarnings>(include_str!("fixtures/today_earnings.json")).is_empty());
+    assert!(unknown::<MarketOhlc>(include_str!("fixtures/market_ohlc.json")).is_empty());
+    assert!(unknown::<MarketPrevious>(include_str!("fixtures/market_previous.json")).is_empty());
+    assert!(unknown::<IncomeStatements>(include_str!("fixtures/income.json")).is_empty());
+    assert!(unknown::<BalanceSheets>(include_str!("fixtures/balance_sheet.json")).is_empty());
+    assert!(unknown::<CashFlows>(include_str!("fixtures/cash_flow.json")).is_empty());
+    assert!(
+        unknown::<BTreeMap<String, Vec<HistEntry>>>(include_str!("fixtures/hist.json")).is_empty()
+    );
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn decimal_prices_are_exact() {
+    let book: Book = serde_json::from_str(include_str!("fixtures/book.json")).unwrap();
+    let spread = book.asks[0].price - book.bids[0].price;
+    assert_eq!(spread, money("0.03"));
+    assert_eq!(spread.to_string(), "0.03");
+}